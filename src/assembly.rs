@@ -5,9 +5,31 @@ use chrono::FixedOffset;
 use serde_json::Value;
 use std::iter::empty;
 
+pub mod activity_log;
 pub mod apim;
+pub mod app_gateway;
+pub mod app_service;
+pub mod application;
+pub mod availability;
 pub mod container_apps;
+pub mod container_instances;
+pub mod cosmos_db;
+pub mod custom_events;
+pub mod data_factory;
+pub mod dependencies;
+pub mod durable;
+pub mod exceptions;
 pub mod functions;
+pub mod identity;
+pub mod key_vault;
+pub mod kubernetes;
+pub mod logic_apps;
+pub mod messaging;
+pub mod spring_apps;
+pub mod sql_database;
+pub mod storage;
+pub mod syslog;
+pub mod windows_event;
 
 pub fn unwrap_as_rfc3339(value: Option<&Value>) -> DateTime<FixedOffset> {
     value
@@ -19,12 +41,103 @@ pub fn unwrap_as_str(value: Option<&Value>) -> &str {
     value.unwrap().as_str().unwrap()
 }
 
+pub fn as_str_or_empty(value: Option<&Value>) -> &str {
+    value.and_then(Value::as_str).unwrap_or("")
+}
+
+/// App Insights delivers dynamic columns such as customDimensions as
+/// JSON-encoded strings, where Log Analytics delivers them as is.
+pub fn as_dynamic(value: Option<&Value>) -> Value {
+    match value {
+        Some(Value::String(s)) => serde_json::from_str(s).unwrap_or(Value::Null),
+        Some(v) => v.clone(),
+        None => Value::Null,
+    }
+}
+
+/// App Insights reports success columns as strings ("True" for requests and
+/// dependencies, "1" for availability results), Log Analytics as booleans.
+pub fn is_success(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::Bool(success)) => *success,
+        Some(Value::String(success)) => success == "1" || success.eq_ignore_ascii_case("true"),
+        Some(Value::Number(success)) => success.as_i64() == Some(1),
+        _ => false,
+    }
+}
+
 pub fn build_sources(opts: &Opts) -> Vec<Box<dyn LogSource>> {
     empty()
         .chain(apim::opsinsights(opts))
+        .chain(apim::opsinsights_exceptions(opts))
+        .chain(apim::opsinsights_gateway(opts))
         .chain(apim::appinsights(opts))
+        .chain(apim::appinsights_exceptions(opts))
         .chain(functions::opsinsights(opts))
+        .chain(functions::opsinsights_exceptions(opts))
         .chain(functions::appinsights(opts))
+        .chain(functions::appinsights_exceptions(opts))
+        .chain(application::opsinsights(opts))
+        .chain(application::appinsights(opts))
+        .chain(dependencies::opsinsights(opts))
+        .chain(dependencies::appinsights(opts))
+        .chain(custom_events::opsinsights(opts))
+        .chain(custom_events::appinsights(opts))
+        .chain(availability::opsinsights(opts))
+        .chain(availability::appinsights(opts))
         .chain(container_apps::opsinsights(opts))
+        .chain(container_apps::opsinsights_system(opts))
+        .chain(container_instances::opsinsights(opts))
+        .chain(logic_apps::opsinsights(opts))
+        .chain(syslog::opsinsights(opts))
+        .chain(windows_event::opsinsights(opts))
+        .chain(app_service::opsinsights_console(opts))
+        .chain(app_service::opsinsights_http(opts))
+        .chain(kubernetes::opsinsights(opts))
+        .chain(kubernetes::opsinsights_events(opts))
+        .chain(kubernetes::opsinsights_audit(opts))
+        .chain(kubernetes::opsinsights_control_plane(opts))
+        .chain(activity_log::opsinsights(opts))
+        .chain(app_gateway::opsinsights_access(opts))
+        .chain(app_gateway::opsinsights_firewall(opts))
+        .chain(identity::opsinsights_signin(opts))
+        .chain(identity::opsinsights_audit(opts))
+        .chain(key_vault::opsinsights(opts))
+        .chain(storage::opsinsights(opts))
+        .chain(messaging::opsinsights(opts))
+        .chain(data_factory::opsinsights(opts))
+        .chain(cosmos_db::opsinsights(opts))
+        .chain(sql_database::opsinsights(opts))
+        .chain(spring_apps::opsinsights(opts))
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use super::is_success;
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn is_success_reads_app_insights_and_log_analytics_values() {
+        for value in [
+            json!(true),
+            json!("True"),
+            json!("true"),
+            json!("1"),
+            json!(1),
+        ] {
+            assert_that(&is_success(Some(&value))).is_true();
+        }
+        for value in [
+            json!(false),
+            json!("False"),
+            json!("0"),
+            json!(0),
+            json!(null),
+        ] {
+            assert_that(&is_success(Some(&value))).is_false();
+        }
+        assert_that(&is_success(None)).is_false();
+    }
+}
@@ -0,0 +1,109 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+fn opsinsights_activity_log_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.resource_group.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.resource_group
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("ResourceGroup".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.caller.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.caller
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Caller".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AzureActivity".to_owned(), timespan, operators)
+}
+
+fn activity_log_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("ResourceGroup")).to_owned();
+    let unit = as_str_or_empty(row.get("_ResourceId"))
+        .rsplit('/')
+        .next()
+        .unwrap()
+        .to_owned();
+    let status = as_str_or_empty(row.get("ActivityStatusValue"));
+    let level = match status {
+        "Failure" | "Failed" => Level::Error,
+        _ => Level::Info,
+    };
+    let message = format!(
+        "{} {} {}",
+        as_str_or_empty(row.get("Caller")),
+        unwrap_as_str(row.get("OperationNameValue")),
+        status
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::ActivityLog) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_activity_log_query(opts),
+        Box::new(activity_log_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{activity_log_row_to_entry, opsinsights_activity_log_query};
+    use crate::{
+        examples::azure_activity_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_maps_failure_to_error() {
+        let mut row = azure_activity_row();
+        assert_that(&activity_log_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("ActivityStatusValue".to_owned(), json!("Failure"));
+        assert_that(&activity_log_row_to_entry(row).level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    pub fn row_to_entry_says_who_changed_what() {
+        let res = activity_log_row_to_entry(azure_activity_row());
+        assert_that(&res.unit()).is_equal_to("aztail-function");
+        assert_that(&res.message())
+            .is_equal_to("jane@example.com MICROSOFT.WEB/SITES/WRITE Success");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--resource-group", "ze-rg"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_activity_log_query(&opts);
+        assert_that!(query.to_string()).contains("ResourceGroup =~ 'ze-rg'");
+    }
+}
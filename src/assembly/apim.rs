@@ -1,14 +1,18 @@
-use crate::assembly::{unwrap_as_rfc3339, unwrap_as_str};
-use crate::kusto::{EndsWith, Filter, Operator, Or, Ordering, Query, StartsWith, Timespan};
+use crate::assembly::exceptions::{exception_message, APPINSIGHTS_COLUMNS, OPSINSIGHTS_COLUMNS};
+use crate::assembly::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+use crate::kusto::{
+    Contains, EndsWith, Eq, EqIgnoreCase, Extend, Filter, Operator, Or, Ordering, Query,
+    StartsWith, Timespan,
+};
 use crate::options::{Opts, Service};
 use crate::source::{appinsight::AppInsights, opsinsight::OpsLogs, Level, LogEntry, LogSource};
 use serde_json::{json, Map, Value};
 
-pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
-    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
-        return None;
-    }
-    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+/// API Management reports to App Insights with a role name such as
+/// "aztail-apim.azure-api.net West Europe".
+pub const APIM_ROLE_MARKER: &str = ".azure-api.net";
+
+fn appinsights_apim_filters(opts: &Opts) -> Vec<Box<dyn Operator>> {
     let mut operators: Vec<Box<dyn Operator>> = Vec::new();
     if !opts.api_name.is_empty() {
         operators.push(Filter::boxed(Or::new(
@@ -21,13 +25,22 @@ pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     }
     if !opts.api_operation.is_empty() {
         operators.push(Filter::boxed(Or::new(
-            opts.api_name
+            opts.api_operation
                 .iter()
                 .cloned()
                 .map(|n| EndsWith::boxed("operation_Name".to_owned(), format!(" {}", n)))
                 .collect(),
         )));
     }
+    operators
+}
+
+pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
+        return None;
+    }
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = appinsights_apim_filters(opts);
     operators.push(Ordering::boxed("timestamp".to_owned()));
     let query = Query::new("requests".to_owned(), timespan, operators);
     Some(AppInsights::boxed(
@@ -37,6 +50,56 @@ pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     ))
 }
 
+fn appinsights_exceptions_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Contains::new(
+        "cloud_RoleName".to_owned(),
+        APIM_ROLE_MARKER.to_owned(),
+    )));
+    operators.extend(appinsights_apim_filters(opts));
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("exceptions".to_owned(), timespan, operators)
+}
+
+pub fn appinsights_exceptions(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
+        return None;
+    }
+    let collapse = opts.collapse_stack_traces;
+    Some(AppInsights::boxed(
+        appinsights_exceptions_query(opts),
+        Box::new(move |row| appinsights_exceptions_row_to_entry(row, collapse)),
+        opts.clone(),
+    ))
+}
+
+fn appinsights_exceptions_row_to_entry(
+    row: Map<String, Value>,
+    collapse_stack_trace: bool,
+) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
+    let group = unwrap_as_str(row.get("cloud_RoleName"))
+        .split('.')
+        .next()
+        .unwrap()
+        .to_owned();
+    let unit = unwrap_as_str(row.get("operation_Name"))
+        .rsplit(' ')
+        .next()
+        .unwrap()
+        .to_owned();
+    let message = exception_message(&row, APPINSIGHTS_COLUMNS, collapse_stack_trace);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Error,
+        message,
+        raw: row,
+    }
+}
+
 pub fn appinsights_requests_row_to_entry(row: Map<String, Value>) -> LogEntry {
     let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
     let group = unwrap_as_str(row.get("cloud_RoleName"))
@@ -103,11 +166,7 @@ fn appinsights_requests_message_line(row: &Map<String, Value>) -> String {
     }
 }
 
-pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
-    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
-        return None;
-    }
-    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+fn opsinsights_apim_filters(opts: &Opts) -> Vec<Box<dyn Operator>> {
     let mut operators: Vec<Box<dyn Operator>> = Vec::new();
     if !opts.api_name.is_empty() {
         operators.push(Filter::boxed(Or::new(
@@ -120,13 +179,22 @@ pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     }
     if !opts.api_operation.is_empty() {
         operators.push(Filter::boxed(Or::new(
-            opts.api_name
+            opts.api_operation
                 .iter()
                 .cloned()
                 .map(|n| EndsWith::boxed("OperationName".to_owned(), format!(" {}", n)))
                 .collect(),
         )));
     }
+    operators
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
+        return None;
+    }
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = opsinsights_apim_filters(opts);
     operators.push(Ordering::boxed("TimeGenerated".to_owned()));
     let query = Query::new("AppRequests".to_owned(), timespan, operators);
     Some(OpsLogs::boxed(
@@ -136,6 +204,56 @@ pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     ))
 }
 
+fn opsinsights_exceptions_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Contains::new(
+        "AppRoleName".to_owned(),
+        APIM_ROLE_MARKER.to_owned(),
+    )));
+    operators.extend(opsinsights_apim_filters(opts));
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppExceptions".to_owned(), timespan, operators)
+}
+
+pub fn opsinsights_exceptions(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
+        return None;
+    }
+    let collapse = opts.collapse_stack_traces;
+    Some(OpsLogs::boxed(
+        opsinsights_exceptions_query(opts),
+        Box::new(move |row| opsinsights_exceptions_row_to_entry(row, collapse)),
+        opts.clone(),
+    ))
+}
+
+fn opsinsights_exceptions_row_to_entry(
+    row: Map<String, Value>,
+    collapse_stack_trace: bool,
+) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("AppRoleName"))
+        .split('.')
+        .next()
+        .unwrap()
+        .to_owned();
+    let unit = unwrap_as_str(row.get("OperationName"))
+        .rsplit(' ')
+        .next()
+        .unwrap()
+        .to_owned();
+    let message = exception_message(&row, OPSINSIGHTS_COLUMNS, collapse_stack_trace);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Error,
+        message,
+        raw: row,
+    }
+}
+
 pub fn opsinsights_requests_row_to_entry(row: Map<String, Value>) -> LogEntry {
     let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
     let group = unwrap_as_str(row.get("AppRoleName"))
@@ -191,13 +309,106 @@ fn opsinsights_requests_message_line(row: &Map<String, Value>) -> String {
     }
 }
 
+/// The gateway diagnostic table identifies operations by their ids rather
+/// than by name, and carries no role name; the service is extracted from
+/// _ResourceId which looks like
+/// /subscriptions/.../providers/microsoft.apimanagement/service/{service}.
+fn opsinsights_gateway_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Extend::boxed(
+        "ApimService".to_owned(),
+        "tolower(tostring(split(_ResourceId, '/')[8]))".to_owned(),
+    ));
+    if !opts.api_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.api_name
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("ApimService".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.api_operation.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.api_operation
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("OperationId".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("ApiManagementGatewayLogs".to_owned(), timespan, operators)
+}
+
+pub fn opsinsights_gateway(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::APIManagement) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_gateway_query(opts),
+        Box::new(gateway_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn gateway_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("ApimService")).to_owned();
+    let unit = unwrap_as_str(row.get("OperationId")).to_owned();
+    let level = match row.get("ResponseCode").and_then(Value::as_i64) {
+        Some(status) if status >= 400 => Level::Warn,
+        _ if !as_str_or_empty(row.get("LastErrorReason")).is_empty() => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = gateway_message_line(&row);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn gateway_message_line(row: &Map<String, Value>) -> String {
+    let client_ip = as_str_or_empty(row.get("CallerIpAddress"));
+    let method = as_str_or_empty(row.get("Method"));
+    let url = as_str_or_empty(row.get("Url"));
+    let status_code = row.get("ResponseCode").unwrap_or(&Value::Null);
+    let backend_status_code = row.get("BackendResponseCode").unwrap_or(&Value::Null);
+    let duration = row.get("TotalTime").unwrap_or(&Value::Null);
+    let mut message = format!(
+        "{} {} \"{}\" {} (backend {}) {}",
+        client_ip, method, url, status_code, backend_status_code, duration
+    );
+    let error_reason = as_str_or_empty(row.get("LastErrorReason"));
+    if !error_reason.is_empty() {
+        message.push_str(&format!(
+            ": {} {}",
+            error_reason,
+            as_str_or_empty(row.get("LastErrorMessage"))
+        ));
+    }
+    message.trim_end().to_owned()
+}
+
 #[cfg(test)]
 mod test {
     use super::appinsights_requests_row_to_entry;
+    use super::opsinsights_exceptions_row_to_entry;
     use super::opsinsights_requests_row_to_entry;
-    use crate::examples::{apprequests_functions_row, requests_http_row};
+    use super::{appinsights_exceptions_query, opsinsights_exceptions_query};
+    use super::{gateway_row_to_entry, opsinsights_gateway_query};
+    use crate::examples::{
+        apim_gateway_row, appexceptions_apim_row, apprequests_functions_row, requests_http_row,
+    };
+    use crate::options::cli_opts;
     use crate::source::Level;
-    use serde_json::Value;
+    use crate::testing::{base_args, opsinsights_base_args};
+    use serde_json::{json, Value};
     use speculoos::prelude::*;
 
     #[test]
@@ -228,4 +439,68 @@ mod test {
         let res = opsinsights_requests_row_to_entry(row);
         assert_that(&res.message()).does_not_contain("\"https://aztail-apim");
     }
+
+    #[test]
+    fn opsinsights_exceptions_row_to_entry_group_and_unit() {
+        let res = opsinsights_exceptions_row_to_entry(appexceptions_apim_row(), true);
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.group()).is_equal_to("aztail-apim");
+        assert_that(&res.unit()).is_equal_to("get-ping");
+        assert_that(&res.message())
+            .is_equal_to("BackendConnectionFailure: Backend connection failure");
+    }
+
+    #[test]
+    fn gateway_row_to_entry_shows_backend_status_and_error() {
+        let res = gateway_row_to_entry(apim_gateway_row());
+        assert_that(&res.group()).is_equal_to("aztail-apim");
+        assert_that(&res.unit()).is_equal_to("get-ping");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to(
+            "83.248.129.91 GET \"https://aztail-apim.azure-api.net/ping\" 502 (backend 0) 20024.4: BackendConnectionFailure The operation was canceled.",
+        );
+    }
+
+    #[test]
+    fn gateway_row_to_entry_success_is_info() {
+        let mut row = apim_gateway_row();
+        row.insert("ResponseCode".to_owned(), json!(200));
+        row.insert("LastErrorReason".to_owned(), json!(""));
+        assert_that(&gateway_row_to_entry(row).level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    fn generates_gateway_query() {
+        let args = opsinsights_base_args().chain(vec!["--api-operation", "get-ping"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_gateway_query(&opts);
+        assert_that!(query.to_string()).starts_with("ApiManagementGatewayLogs");
+        assert_that!(query.to_string()).contains("OperationId == 'get-ping'");
+    }
+
+    #[test]
+    fn api_name_matches_the_service_in_every_source() {
+        let args = base_args().chain(vec!["--api-name", "aztail-apim"]);
+        let query = appinsights_exceptions_query(&cli_opts(args).unwrap()).to_string();
+        assert_that!(query).contains("cloud_RoleName startswith_cs 'aztail-apim.'");
+        let args = opsinsights_base_args().chain(vec!["--api-name", "aztail-apim"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_exceptions_query(&opts).to_string();
+        assert_that!(query).contains("AppRoleName startswith_cs 'aztail-apim.'");
+        let query = opsinsights_gateway_query(&opts).to_string();
+        assert_that!(query).contains("ApimService =~ 'aztail-apim'");
+        assert_that!(query).does_not_contain("ApiId");
+    }
+
+    #[test]
+    fn exceptions_queries_filter_on_api_operation_alone() {
+        let args = base_args().chain(vec!["--api-operation", "get-ping"]);
+        let query = appinsights_exceptions_query(&cli_opts(args).unwrap()).to_string();
+        assert_that!(query).contains("operation_Name endswith_cs ' get-ping'");
+        assert_that!(query).does_not_contain("where ()");
+        let args = opsinsights_base_args().chain(vec!["--api-operation", "get-ping"]);
+        let query = opsinsights_exceptions_query(&cli_opts(args).unwrap()).to_string();
+        assert_that!(query).contains("OperationName endswith_cs ' get-ping'");
+        assert_that!(query).does_not_contain("where ()");
+    }
 }
@@ -0,0 +1,211 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+const ACCESS_CATEGORIES: [&str; 2] = ["ApplicationGatewayAccessLog", "FrontDoorAccessLog"];
+const FIREWALL_CATEGORIES: [&str; 2] = [
+    "ApplicationGatewayFirewallLog",
+    "FrontDoorWebApplicationFirewallLog",
+];
+
+fn opsinsights_gateway_filters(opts: &Opts, categories: &[&str]) -> Vec<Box<dyn Operator>> {
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Or::new(
+        categories
+            .iter()
+            .map(|c| Eq::boxed("Category".to_owned(), (*c).to_owned()))
+            .collect(),
+    )));
+    if !opts.gateway.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.gateway
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Resource".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators
+}
+
+fn opsinsights_access_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = opsinsights_gateway_filters(opts, &ACCESS_CATEGORIES);
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AzureDiagnostics".to_owned(), timespan, operators)
+}
+
+fn opsinsights_firewall_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = opsinsights_gateway_filters(opts, &FIREWALL_CATEGORIES);
+    if !opts.waf_action.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.waf_action
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("action_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AzureDiagnostics".to_owned(), timespan, operators)
+}
+
+/// Application Gateway and Front Door name their columns slightly
+/// differently, so each datum is looked up in the first column present.
+fn first_of(row: &Map<String, Value>, columns: &[&str]) -> String {
+    match columns
+        .iter()
+        .filter_map(|c| row.get(*c))
+        .find(|v| !v.is_null() && v.as_str() != Some(""))
+    {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 => format!("{}", f as i64),
+            _ => n.to_string(),
+        },
+        Some(v) => v.to_string(),
+        None => "-".to_owned(),
+    }
+}
+
+fn access_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("Resource")).to_lowercase();
+    let unit = first_of(&row, &["ruleName_s", "routingRuleName_s"]);
+    let level = match first_of(&row, &["httpStatus_d", "httpStatusCode_s"]).parse::<i64>() {
+        Ok(status) if status >= 400 => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = access_message_line(&row);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn access_message_line(row: &Map<String, Value>) -> String {
+    let client_ip = first_of(row, &["clientIP_s", "clientIp_s"]);
+    let method = first_of(row, &["httpMethod_s"]);
+    let url = match as_str_or_empty(row.get("requestQuery_s")) {
+        "" => first_of(row, &["requestUri_s"]),
+        query => format!("{}?{}", first_of(row, &["requestUri_s"]), query),
+    };
+    let status_code = first_of(row, &["httpStatus_d", "httpStatusCode_s"]);
+    let response_size = first_of(row, &["sentBytes_d", "responseBytes_s"]);
+    let duration = first_of(row, &["timeTaken_d", "timeTaken_s"]);
+    format!(
+        "{} {} \"{}\" {} {} {}",
+        client_ip, method, url, status_code, response_size, duration
+    )
+}
+
+fn firewall_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("Resource")).to_lowercase();
+    let unit = first_of(&row, &["ruleId_s", "ruleName_s"]);
+    let level = match as_str_or_empty(row.get("action_s")) {
+        "Blocked" | "Block" => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = firewall_message_line(&row);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn firewall_message_line(row: &Map<String, Value>) -> String {
+    let client_ip = first_of(row, &["clientIp_s", "clientIP_s"]);
+    let action = first_of(row, &["action_s"]);
+    let url = first_of(row, &["requestUri_s"]);
+    let mut message = format!("{} {} \"{}\"", client_ip, action, url);
+    let reason = as_str_or_empty(row.get("Message"));
+    if !reason.is_empty() {
+        message.push_str(&format!(": {}", reason));
+    }
+    message
+}
+
+pub fn opsinsights_access(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || !opts.waf_action.is_empty()
+        || !opts.requested_services().contains(&Service::AppGateway)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_access_query(opts),
+        Box::new(access_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+pub fn opsinsights_firewall(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::AppGateway) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_firewall_query(opts),
+        Box::new(firewall_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        access_row_to_entry, firewall_row_to_entry, opsinsights_access, opsinsights_firewall_query,
+    };
+    use crate::{
+        examples::{app_gateway_access_row, front_door_firewall_row},
+        options::cli_opts,
+        source::Level,
+        testing::opsinsights_base_args,
+    };
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn access_row_to_entry_is_access_log_line() {
+        let res = access_row_to_entry(app_gateway_access_row());
+        assert_that(&res.group()).is_equal_to("aztail-agw");
+        assert_that(&res.unit()).is_equal_to("api-rule");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message())
+            .is_equal_to("83.248.129.91 GET \"/api/ping?foo=bar\" 403 345 0.004");
+    }
+
+    #[test]
+    pub fn firewall_row_to_entry_warns_on_blocked_request() {
+        let res = firewall_row_to_entry(front_door_firewall_row());
+        assert_that(&res.group()).is_equal_to("aztail-fd");
+        assert_that(&res.unit()).is_equal_to("DefaultRuleSet-1.0-SQLI-942100");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to(
+            "83.248.129.91 Block \"https://aztail-fd.azurefd.net:443/api/ping?id=1%27%20OR%201=1\"",
+        );
+    }
+
+    #[test]
+    pub fn waf_action_restricts_to_firewall_logs() {
+        let args = opsinsights_base_args().chain(vec!["--waf-action", "Blocked"]);
+        let opts = cli_opts(args).unwrap();
+        assert_that(&opsinsights_access(&opts).into_iter().count()).is_equal_to(0);
+        let query = opsinsights_firewall_query(&opts);
+        assert_that!(query.to_string()).contains("action_s =~ 'Blocked'");
+    }
+}
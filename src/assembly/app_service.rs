@@ -0,0 +1,166 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{EqIgnoreCase, Extend, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+/// App Service tables carry no app name column; the app and slot are
+/// extracted from _ResourceId which looks like
+/// /subscriptions/.../providers/microsoft.web/sites/{app}[/slots/{slot}].
+fn opsinsights_app_service_query(opts: &Opts, table: &str) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Extend::boxed(
+        "WebApp".to_owned(),
+        "tolower(tostring(split(_ResourceId, '/')[8]))".to_owned(),
+    ));
+    operators.push(Extend::boxed(
+        "Slot".to_owned(),
+        "tolower(coalesce(tostring(split(_ResourceId, '/')[10]), 'production'))".to_owned(),
+    ));
+    if !opts.web_app.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.web_app
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("WebApp".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.slot.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.slot
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Slot".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new(table.to_owned(), timespan, operators)
+}
+
+fn console_logs_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("WebApp")).to_owned();
+    let unit = unwrap_as_str(row.get("Slot")).to_owned();
+    let level = match as_str_or_empty(row.get("Level")) {
+        "Error" => Level::Error,
+        "Warning" => Level::Warn,
+        "Verbose" => Level::Verbose,
+        _ => Level::Info,
+    };
+    let message = unwrap_as_str(row.get("ResultDescription"))
+        .trim_end()
+        .to_owned();
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn http_logs_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("WebApp")).to_owned();
+    let unit = unwrap_as_str(row.get("Slot")).to_owned();
+    let level = match row.get("ScStatus").and_then(Value::as_i64) {
+        Some(status) if status >= 400 => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = http_logs_message_line(&row);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn http_logs_message_line(row: &Map<String, Value>) -> String {
+    let client_ip = unwrap_as_str(row.get("CIp"));
+    let method = unwrap_as_str(row.get("CsMethod"));
+    let url = match as_str_or_empty(row.get("CsUriQuery")) {
+        "" | "-" => unwrap_as_str(row.get("CsUriStem")).to_owned(),
+        query => format!("{}?{}", unwrap_as_str(row.get("CsUriStem")), query),
+    };
+    let status_code = row.get("ScStatus").unwrap();
+    let response_size = row.get("ScBytes").unwrap();
+    let duration = row.get("TimeTaken").unwrap();
+    format!(
+        "{} {} \"{}\" {} {} {}",
+        client_ip, method, url, status_code, response_size, duration
+    )
+}
+
+pub fn opsinsights_console(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::WebApps) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_app_service_query(opts, "AppServiceConsoleLogs"),
+        Box::new(console_logs_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+pub fn opsinsights_http(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::WebApps) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_app_service_query(opts, "AppServiceHTTPLogs"),
+        Box::new(http_logs_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{console_logs_row_to_entry, http_logs_row_to_entry, opsinsights_app_service_query};
+    use crate::{
+        examples::{app_service_console_row, app_service_http_row},
+        options::cli_opts,
+        source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn console_row_to_entry_sets_level() {
+        let mut row = app_service_console_row();
+        assert_that(&console_logs_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("Level".to_owned(), json!("Error"));
+        assert_that(&console_logs_row_to_entry(row).level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    pub fn http_row_to_entry_is_access_log_line() {
+        let res = http_logs_row_to_entry(app_service_http_row());
+        assert_that(&res.group()).is_equal_to("aztail-webapp");
+        assert_that(&res.unit()).is_equal_to("staging");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message())
+            .is_equal_to("83.248.129.91 GET \"/api/ping?foo=bar\" 404 1245 31");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--web-app", "ze-app", "--slot", "staging"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_app_service_query(&opts, "AppServiceHTTPLogs");
+        assert_that!(query.to_string()).starts_with("AppServiceHTTPLogs");
+        assert_that!(query.to_string()).contains("WebApp =~ 'ze-app'");
+        assert_that!(query.to_string()).contains("Slot =~ 'staging'");
+    }
+}
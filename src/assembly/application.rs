@@ -0,0 +1,176 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    options::{Opts, Service},
+    source::{appinsight::AppInsights, opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_dynamic, as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+/// Dimension keys under which the ASP.NET Core, Java and Node SDKs record
+/// the logger category.
+const CATEGORY_DIMENSIONS: [&str; 3] = ["CategoryName", "LoggerName", "Category"];
+
+fn application_filters(opts: &Opts, role: &str, role_instance: &str) -> Vec<Box<dyn Operator>> {
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.role.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.role
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed(role.to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.role_instance.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.role_instance
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed(role_instance.to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators
+}
+
+fn appinsights_application_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = application_filters(opts, "cloud_RoleName", "cloud_RoleInstance");
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("traces".to_owned(), timespan, operators)
+}
+
+fn opsinsights_application_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = application_filters(opts, "AppRoleName", "AppRoleInstance");
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppTraces".to_owned(), timespan, operators)
+}
+
+pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::Application) {
+        return None;
+    }
+    Some(AppInsights::boxed(
+        appinsights_application_query(opts),
+        Box::new(traces_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Application) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_application_query(opts),
+        Box::new(apptraces_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn severity_to_level(severity: Option<&Value>) -> Level {
+    match severity.and_then(Value::as_i64) {
+        Some(3) | Some(4) => Level::Error,
+        Some(2) => Level::Warn,
+        Some(1) | None => Level::Info,
+        Some(_) => Level::Verbose,
+    }
+}
+
+fn sdk_category(dimensions: Option<&Value>) -> String {
+    let dimensions = as_dynamic(dimensions);
+    CATEGORY_DIMENSIONS
+        .iter()
+        .map(|key| as_str_or_empty(dimensions.get(key)))
+        .find(|category| !category.is_empty())
+        .unwrap_or("")
+        .to_owned()
+}
+
+fn application_message(category: String, message: &str) -> String {
+    if category.is_empty() {
+        message.to_owned()
+    } else {
+        format!("[{}] {}", category, message)
+    }
+}
+
+fn traces_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
+    let group = unwrap_as_str(row.get("cloud_RoleName")).to_owned();
+    let unit = as_str_or_empty(row.get("cloud_RoleInstance")).to_owned();
+    let level = severity_to_level(row.get("severityLevel"));
+    let message = application_message(
+        sdk_category(row.get("customDimensions")),
+        unwrap_as_str(row.get("message")),
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn apptraces_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("AppRoleName")).to_owned();
+    let unit = as_str_or_empty(row.get("AppRoleInstance")).to_owned();
+    let level = severity_to_level(row.get("SeverityLevel"));
+    let message = application_message(
+        sdk_category(row.get("Properties")),
+        unwrap_as_str(row.get("Message")),
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apptraces_row_to_entry, opsinsights_application_query, traces_row_to_entry};
+    use crate::{
+        examples::{apptraces_application_row, traces_application_row},
+        options::cli_opts,
+        source::Level,
+        testing::opsinsights_base_args,
+    };
+    use speculoos::prelude::*;
+
+    #[test]
+    fn traces_row_to_entry_shows_instance_and_category() {
+        let res = traces_row_to_entry(traces_application_row());
+        assert_that(&res.group()).is_equal_to("orders-api");
+        assert_that(&res.unit()).is_equal_to("orders-api-7d9f8b6c4-x2lkq");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message())
+            .is_equal_to("[Orders.Controllers.OrderController] Order 4711 not found");
+    }
+
+    #[test]
+    fn apptraces_row_to_entry_java_logger_name() {
+        let res = apptraces_row_to_entry(apptraces_application_row());
+        assert_that(&res.unit()).is_equal_to("billing-5c6d7f9b8-qwz4r");
+        assert_that(&res.level()).is_equal_to(Level::Info);
+        assert_that(&res.message())
+            .is_equal_to("[com.example.billing.InvoiceService] Invoice 17 sent");
+    }
+
+    #[test]
+    fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--role-instance", "ze-instance"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_application_query(&opts);
+        assert_that!(query.to_string()).contains("AppRoleInstance == 'ze-instance'");
+    }
+}
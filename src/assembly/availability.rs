@@ -0,0 +1,164 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    options::{Opts, Service},
+    source::{appinsight::AppInsights, opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, is_success, unwrap_as_rfc3339, unwrap_as_str};
+
+fn appinsights_availability_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.test_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.test_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("name".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("availabilityResults".to_owned(), timespan, operators)
+}
+
+pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::Availability) {
+        return None;
+    }
+    Some(AppInsights::boxed(
+        appinsights_availability_query(opts),
+        Box::new(availability_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn availability_message(success: bool, duration: Option<&Value>, message: &str) -> String {
+    let mut line = format!(
+        "{} {} ms",
+        if success { "passed" } else { "failed" },
+        duration.unwrap_or(&Value::Null)
+    );
+    if !message.is_empty() {
+        line.push_str(&format!(": {}", message.trim_end()));
+    }
+    line
+}
+
+fn availability_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
+    let group = unwrap_as_str(row.get("name")).to_owned();
+    let unit = as_str_or_empty(row.get("location")).to_owned();
+    let success = is_success(row.get("success"));
+    let level = if success { Level::Info } else { Level::Error };
+    let message = availability_message(
+        success,
+        row.get("duration"),
+        as_str_or_empty(row.get("message")),
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn opsinsights_availability_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.test_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.test_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Name".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppAvailabilityResults".to_owned(), timespan, operators)
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Availability) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_availability_query(opts),
+        Box::new(appavailability_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn appavailability_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("Name")).to_owned();
+    let unit = as_str_or_empty(row.get("Location")).to_owned();
+    let success = is_success(row.get("Success"));
+    let level = if success { Level::Info } else { Level::Error };
+    let message = availability_message(
+        success,
+        row.get("DurationMs"),
+        as_str_or_empty(row.get("Message")),
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{appavailability_row_to_entry, availability_row_to_entry};
+    use crate::{
+        examples::{appavailability_row, availability_row},
+        options::cli_opts,
+        source::Level,
+        testing::{base_args, opsinsights_base_args},
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn availability_row_to_entry_failure_is_error() {
+        let res = availability_row_to_entry(availability_row());
+        assert_that(&res.group()).is_equal_to("orders-api ping");
+        assert_that(&res.unit()).is_equal_to("West Europe");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.message()).is_equal_to(
+            "failed 30000 ms: Request timed out after 30 seconds. Timeout was reached.",
+        );
+    }
+
+    #[test]
+    fn availability_row_to_entry_success_is_info() {
+        let mut row = availability_row();
+        row.insert("success".to_owned(), json!("1"));
+        row.insert("message".to_owned(), json!("Passed"));
+        assert_that(&availability_row_to_entry(row).level()).is_equal_to(Level::Info);
+        let mut row = appavailability_row();
+        row.insert("Success".to_owned(), json!(true));
+        assert_that(&appavailability_row_to_entry(row).level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    fn queries_respect_test_name() {
+        let args = base_args().chain(vec!["--test-name", "orders-api ping"]);
+        let opts = cli_opts(args).unwrap();
+        let query = super::appinsights_availability_query(&opts);
+        assert_that(&query.to_string()).contains("name == 'orders-api ping'");
+        let args = opsinsights_base_args().chain(vec!["--test-name", "orders-api ping"]);
+        let opts = cli_opts(args).unwrap();
+        let query = super::opsinsights_availability_query(&opts);
+        assert_that(&query.to_string()).contains("Name == 'orders-api ping'");
+    }
+}
@@ -2,11 +2,21 @@ use serde_json::{Map, Value};
 
 use crate::options::{Opts, Service};
 use crate::{
-    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan, Union},
     source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
 };
 
-use super::{unwrap_as_rfc3339, unwrap_as_str};
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+/// Environments created before the resource-specific tables were introduced
+/// write console logs to the custom table, with suffixed column names.
+const CONSOLE_LOGS_CL: &str = "ContainerAppConsoleLogs_CL \
+    | project-rename ContainerAppName = ContainerAppName_s, \
+    ContainerGroupName = ContainerGroupName_s, ContainerName = ContainerName_s, \
+    RevisionName = RevisionName_s, Stream = Stream_s, Log = Log_s";
+
+const CONSOLE_LOGS: &str = "ContainerAppConsoleLogs \
+    | extend ContainerGroupName = column_ifexists('ContainerGroupName', RevisionName)";
 
 fn opsinsights_container_apps_query(opts: &Opts) -> Query {
     let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
@@ -16,7 +26,7 @@ fn opsinsights_container_apps_query(opts: &Opts) -> Query {
             opts.container_group
                 .iter()
                 .cloned()
-                .map(|n| Eq::boxed("ContainerAppName_s".to_owned(), n))
+                .map(|n| Eq::boxed("ContainerAppName".to_owned(), n))
                 .collect(),
         )));
     }
@@ -25,28 +35,29 @@ fn opsinsights_container_apps_query(opts: &Opts) -> Query {
             opts.container_name
                 .iter()
                 .cloned()
-                .map(|n| Eq::boxed("ContainerName_s".to_owned(), n))
+                .map(|n| Eq::boxed("ContainerName".to_owned(), n))
                 .collect(),
         )));
     }
     operators.push(Ordering::boxed("TimeGenerated".to_owned()));
-    Query::new("ContainerAppConsoleLogs_CL".to_owned(), timespan, operators)
+    let tables = Union::new(vec![CONSOLE_LOGS_CL.to_owned(), CONSOLE_LOGS.to_owned()]);
+    Query::new(tables.to_string(), timespan, operators)
 }
 
 fn container_apps_row_to_entry(row: Map<String, Value>) -> LogEntry {
     let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
-    let group = unwrap_as_str(row.get("ContainerAppName_s")).to_owned();
-    let unit = unwrap_as_str(row.get("ContainerGroupName_s"))
+    let group = unwrap_as_str(row.get("ContainerAppName")).to_owned();
+    let unit = unwrap_as_str(row.get("ContainerGroupName"))
         .replace(&group, "")
         .chars()
         .skip_while(|c| *c == '-')
         .collect();
-    let level = match row.get("Stream_s").unwrap().as_str() {
+    let level = match row.get("Stream").unwrap().as_str() {
         Some(s) if s == "stderr" => Level::Error,
         Some(_) => Level::Info,
         None => Level::Info,
     };
-    let message = unwrap_as_str(row.get("Log_s")).to_owned();
+    let message = unwrap_as_str(row.get("Log")).to_owned();
     LogEntry {
         timestamp,
         group,
@@ -58,7 +69,10 @@ fn container_apps_row_to_entry(row: Map<String, Value>) -> LogEntry {
 }
 
 pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
-    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::ContainerApps) {
+    if opts.workspace.is_none()
+        || opts.system_only
+        || !opts.requested_services().contains(&Service::ContainerApps)
+    {
         return None;
     }
     Some(OpsLogs::boxed(
@@ -68,20 +82,123 @@ pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     ))
 }
 
+/// Older environments write system logs to the custom table, newer ones to
+/// the resource-specific table.
+const SYSTEM_LOGS_CL: &str = "ContainerAppSystemLogs_CL \
+    | project TimeGenerated, ContainerAppName = ContainerAppName_s, \
+    RevisionName = RevisionName_s, ReplicaName = ReplicaName_s, \
+    EventType = Type_s, Reason = Reason_s, Log = Log_s";
+
+const SYSTEM_LOGS: &str = "ContainerAppSystemLogs \
+    | project TimeGenerated, ContainerAppName, RevisionName, ReplicaName, \
+    EventType = column_ifexists('Level', ''), Reason, Log";
+
+fn opsinsights_system_logs_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.container_group.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.container_group
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("ContainerAppName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![SYSTEM_LOGS_CL.to_owned(), SYSTEM_LOGS.to_owned()]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn system_logs_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("ContainerAppName")).to_owned();
+    let unit = match as_str_or_empty(row.get("ReplicaName")) {
+        "" => as_str_or_empty(row.get("RevisionName")),
+        replica => replica,
+    }
+    .replace(&group, "")
+    .chars()
+    .skip_while(|c| *c == '-')
+    .collect();
+    let reason = as_str_or_empty(row.get("Reason"));
+    let level = if ["Fail", "Error", "Crash", "BackOff"]
+        .iter()
+        .any(|r| reason.contains(r))
+    {
+        Level::Error
+    } else if as_str_or_empty(row.get("EventType")) == "Warning" {
+        Level::Warn
+    } else {
+        Level::Info
+    };
+    let message = format!("{}: {}", reason, as_str_or_empty(row.get("Log")));
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights_system(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || opts.console_only
+        || !opts.requested_services().contains(&Service::ContainerApps)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_system_logs_query(opts),
+        Box::new(system_logs_row_to_entry),
+        opts.clone(),
+    ))
+}
+
 #[cfg(test)]
 mod test {
-    use super::{container_apps_row_to_entry, opsinsights_container_apps_query};
+    use super::{
+        container_apps_row_to_entry, opsinsights_container_apps_query,
+        opsinsights_system_logs_query, system_logs_row_to_entry,
+    };
     use crate::{
-        examples::container_apps_row, options::cli_opts, source::Level,
+        examples::{
+            container_apps_renamed_row, container_apps_resource_specific_row, container_apps_row,
+            container_apps_system_row,
+        },
+        options::cli_opts,
+        source::Level,
         testing::opsinsights_base_args,
     };
+    use serde_json::json;
     use speculoos::prelude::*;
 
     #[test]
     pub fn row_to_entry_sets_level() {
-        let row = container_apps_row();
+        let row = container_apps_renamed_row();
         let res = container_apps_row_to_entry(row);
+        assert_that(&res.group()).is_equal_to("aztail-containerapp");
+        assert_that(&res.unit()).is_equal_to("50rufil-6f767b7c8d-wjnh9");
         assert_that(&res.level()).is_equal_to(Level::Info);
+        assert_that(&res.message()).is_equal_to("Stdout");
+    }
+
+    #[test]
+    pub fn custom_table_rename_matches_row_to_entry_columns() {
+        let row = container_apps_row();
+        let renamed = container_apps_renamed_row();
+        for (to, from) in super::CONSOLE_LOGS_CL
+            .split("project-rename")
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .map(|pair| pair.split_once('=').unwrap())
+        {
+            assert_that!(row.get(from.trim())).is_some();
+            assert_that!(renamed.get(to.trim())).is_equal_to(row.get(from.trim()));
+        }
     }
 
     #[test]
@@ -91,4 +208,51 @@ mod test {
         let query = opsinsights_container_apps_query(&opts);
         assert_that!(query.to_string()).contains("ze-container");
     }
+
+    #[test]
+    pub fn query_covers_both_console_log_schemas() {
+        let opts = cli_opts(opsinsights_base_args()).unwrap();
+        let query = opsinsights_container_apps_query(&opts).to_string();
+        assert_that!(query).contains("(ContainerAppConsoleLogs_CL | project-rename");
+        assert_that!(query).contains("(ContainerAppConsoleLogs | extend");
+    }
+
+    #[test]
+    pub fn row_to_entry_resource_specific_schema() {
+        let res = container_apps_row_to_entry(container_apps_resource_specific_row());
+        assert_that(&res.group()).is_equal_to("aztail-containerapp");
+        assert_that(&res.unit()).is_equal_to("50rufil-6f767b7c8d-wjnh9");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.message()).is_equal_to("Stderr");
+    }
+
+    #[test]
+    pub fn system_row_to_entry_maps_reason_to_level() {
+        let mut row = container_apps_system_row();
+        let res = system_logs_row_to_entry(row.clone());
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.unit()).is_equal_to("50rufil-6f767b7c8d-wjnh9");
+        row.insert("Reason".to_owned(), json!("ContainerTerminated"));
+        row.insert("EventType".to_owned(), json!("Warning"));
+        assert_that(&system_logs_row_to_entry(row.clone()).level()).is_equal_to(Level::Warn);
+        row.insert("EventType".to_owned(), json!("Normal"));
+        assert_that(&system_logs_row_to_entry(row).level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    pub fn generates_system_logs_query() {
+        let args = opsinsights_base_args().chain(vec!["--container-group", "ze-app"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_system_logs_query(&opts);
+        assert_that!(query.to_string()).contains("(ContainerAppSystemLogs_CL | project");
+        assert_that!(query.to_string()).contains("ContainerAppName == 'ze-app'");
+    }
+
+    #[test]
+    pub fn console_and_system_only_are_exclusive() {
+        let args = opsinsights_base_args().chain(vec!["--system-only"]);
+        let opts = cli_opts(args).unwrap();
+        assert_that!(super::opsinsights(&opts).into_iter().count()).is_equal_to(0);
+        assert_that!(super::opsinsights_system(&opts).into_iter().count()).is_equal_to(1);
+    }
 }
@@ -0,0 +1,89 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{unwrap_as_rfc3339, unwrap_as_str};
+
+fn opsinsights_container_instances_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.container_group.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.container_group
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("ContainerGroup_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.container_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.container_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("ContainerName_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("ContainerInstanceLog_CL".to_owned(), timespan, operators)
+}
+
+fn container_instances_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("ContainerGroup_s")).to_owned();
+    let unit = unwrap_as_str(row.get("ContainerName_s")).to_owned();
+    let message = unwrap_as_str(row.get("Message")).to_owned();
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Info,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || !opts
+            .requested_services()
+            .contains(&Service::ContainerInstances)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_container_instances_query(opts),
+        Box::new(container_instances_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{container_instances_row_to_entry, opsinsights_container_instances_query};
+    use crate::{
+        examples::container_instances_row, options::cli_opts, testing::opsinsights_base_args,
+    };
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_group_and_unit() {
+        let res = container_instances_row_to_entry(container_instances_row());
+        assert_that(&res.group()).is_equal_to("aztail-container");
+        assert_that(&res.unit()).is_equal_to("log-container");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--container-group", "ze-group"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_container_instances_query(&opts);
+        assert_that!(query.to_string()).starts_with("ContainerInstanceLog_CL");
+        assert_that!(query.to_string()).contains("ContainerGroup_s == 'ze-group'");
+    }
+}
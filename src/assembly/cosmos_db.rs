@@ -0,0 +1,145 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+const DATA_PLANE_REQUESTS: &str = "CDBDataPlaneRequests \
+    | project TimeGenerated, AccountName = tolower(AccountName), DatabaseName, \
+    CollectionName, OperationName, StatusCode = toint(StatusCode), \
+    RequestCharge = todouble(RequestCharge), DurationMs = todouble(DurationMs), \
+    ClientIpAddress";
+
+/// Accounts that still use AzureDiagnostics log the same data in string
+/// columns, so they are cast to match the resource-specific table.
+const AZURE_DIAGNOSTICS: &str = "AzureDiagnostics \
+    | where Category == 'DataPlaneRequests' \
+    | project TimeGenerated, AccountName = tolower(accountName_s), \
+    DatabaseName = tostring(column_ifexists('databaseName_s', '')), \
+    CollectionName = tostring(column_ifexists('collectionName_s', '')), OperationName, \
+    StatusCode = toint(statusCode_s), RequestCharge = todouble(requestCharge_s), \
+    DurationMs = todouble(column_ifexists('duration_s', '')), \
+    ClientIpAddress = tostring(column_ifexists('clientIpAddress_s', ''))";
+
+fn opsinsights_cosmos_db_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.cosmos_account.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.cosmos_account
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("AccountName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.database.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.database
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("DatabaseName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.collection.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.collection
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("CollectionName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![
+        DATA_PLANE_REQUESTS.to_owned(),
+        AZURE_DIAGNOSTICS.to_owned(),
+    ]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn cosmos_db_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("AccountName")).to_owned();
+    let unit = format!(
+        "{}/{}",
+        as_str_or_empty(row.get("DatabaseName")),
+        as_str_or_empty(row.get("CollectionName"))
+    );
+    let status_code = row.get("StatusCode").and_then(Value::as_i64);
+    let level = match status_code {
+        Some(429) => Level::Warn,
+        Some(status) if status >= 500 => Level::Error,
+        _ => Level::Info,
+    };
+    let message = format!(
+        "{} {} {} {} RU {} ms",
+        as_str_or_empty(row.get("ClientIpAddress")),
+        as_str_or_empty(row.get("OperationName")),
+        status_code.map_or("-".to_owned(), |s| s.to_string()),
+        row.get("RequestCharge").unwrap_or(&Value::Null),
+        row.get("DurationMs").unwrap_or(&Value::Null)
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message: message.trim_start().to_owned(),
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::CosmosDb) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_cosmos_db_query(opts),
+        Box::new(cosmos_db_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cosmos_db_row_to_entry, opsinsights_cosmos_db_query};
+    use crate::{
+        examples::cosmos_db_request_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_throttling_is_warn() {
+        let res = cosmos_db_row_to_entry(cosmos_db_request_row());
+        assert_that(&res.group()).is_equal_to("aztail-cosmos");
+        assert_that(&res.unit()).is_equal_to("orders/items");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to("20.50.2.17 Query 429 2.86 RU 5.2 ms");
+    }
+
+    #[test]
+    pub fn row_to_entry_success_is_info() {
+        let mut row = cosmos_db_request_row();
+        row.insert("StatusCode".to_owned(), json!(200));
+        assert_that(&cosmos_db_row_to_entry(row).level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args =
+            opsinsights_base_args().chain(vec!["--database", "orders", "--collection", "items"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_cosmos_db_query(&opts).to_string();
+        assert_that!(query).starts_with("union isfuzzy=true (CDBDataPlaneRequests");
+        assert_that!(query).contains("DatabaseName == 'orders'");
+        assert_that!(query).contains("CollectionName == 'items'");
+    }
+}
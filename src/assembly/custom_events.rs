@@ -0,0 +1,163 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    options::{Opts, Service},
+    source::{appinsight::AppInsights, opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_dynamic, as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+fn appinsights_custom_events_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.event_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.event_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("name".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("customEvents".to_owned(), timespan, operators)
+}
+
+pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::CustomEvents) {
+        return None;
+    }
+    let keys = opts.event_dimension.clone();
+    Some(AppInsights::boxed(
+        appinsights_custom_events_query(opts),
+        Box::new(move |row| custom_events_row_to_entry(row, &keys)),
+        opts.clone(),
+    ))
+}
+
+/// Formats the dimensions named in `keys` as `key=value` pairs, in that
+/// order, skipping the ones the event does not carry.
+fn dimensions_line(dimensions: Option<&Value>, keys: &[String]) -> String {
+    let dimensions = as_dynamic(dimensions);
+    keys.iter()
+        .filter_map(|key| match dimensions.get(key)? {
+            Value::String(s) => Some(format!("{}={}", key, s)),
+            v => Some(format!("{}={}", key, v)),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn custom_events_row_to_entry(row: Map<String, Value>, keys: &[String]) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
+    let group = as_str_or_empty(row.get("cloud_RoleName")).to_owned();
+    let unit = as_str_or_empty(row.get("operation_Name")).to_owned();
+    let message = format!(
+        "{} {}",
+        unwrap_as_str(row.get("name")),
+        dimensions_line(row.get("customDimensions"), keys)
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Info,
+        message: message.trim_end().to_owned(),
+        raw: row,
+    }
+}
+
+fn opsinsights_custom_events_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.event_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.event_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Name".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppEvents".to_owned(), timespan, operators)
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::CustomEvents) {
+        return None;
+    }
+    let keys = opts.event_dimension.clone();
+    Some(OpsLogs::boxed(
+        opsinsights_custom_events_query(opts),
+        Box::new(move |row| appevents_row_to_entry(row, &keys)),
+        opts.clone(),
+    ))
+}
+
+fn appevents_row_to_entry(row: Map<String, Value>, keys: &[String]) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("AppRoleName")).to_owned();
+    let unit = as_str_or_empty(row.get("OperationName")).to_owned();
+    let message = format!(
+        "{} {}",
+        unwrap_as_str(row.get("Name")),
+        dimensions_line(row.get("Properties"), keys)
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Info,
+        message: message.trim_end().to_owned(),
+        raw: row,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        appevents_row_to_entry, appinsights_custom_events_query, custom_events_row_to_entry,
+    };
+    use crate::{
+        examples::{appevents_row, custom_events_row},
+        options::cli_opts,
+        testing::base_args,
+    };
+    use speculoos::prelude::*;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|k| (*k).to_owned()).collect()
+    }
+
+    #[test]
+    fn custom_events_row_to_entry_shows_selected_dimensions() {
+        let res = custom_events_row_to_entry(custom_events_row(), &keys(&["OrderId", "Channel"]));
+        assert_that(&res.group()).is_equal_to("ze-app");
+        assert_that(&res.message()).is_equal_to("OrderPlaced OrderId=4711 Channel=web");
+    }
+
+    #[test]
+    fn appevents_row_to_entry_shows_selected_dimensions() {
+        let res = appevents_row_to_entry(appevents_row(), &keys(&["Channel", "Missing"]));
+        assert_that(&res.unit()).is_equal_to("POST Orders/Create");
+        assert_that(&res.message()).is_equal_to("OrderPlaced Channel=web");
+    }
+
+    #[test]
+    fn row_to_entry_shows_only_name_by_default() {
+        let res = custom_events_row_to_entry(custom_events_row(), &[]);
+        assert_that(&res.message()).is_equal_to("OrderPlaced");
+        let res = appevents_row_to_entry(appevents_row(), &[]);
+        assert_that(&res.message()).is_equal_to("OrderPlaced");
+    }
+
+    #[test]
+    fn queries_respect_event_name() {
+        let args = base_args().chain(vec!["--event-name", "OrderPlaced"]);
+        let opts = cli_opts(args).unwrap();
+        let query = appinsights_custom_events_query(&opts);
+        assert_that(&query.to_string()).contains("name == 'OrderPlaced'");
+    }
+}
@@ -0,0 +1,138 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+/// Pipeline and activity runs log one row per status transition. Activity
+/// runs are keyed on their pipeline run so that they interleave with it.
+const PIPELINE_RUNS: &str = "ADFPipelineRun \
+    | project TimeGenerated, Factory = tolower(tostring(split(_ResourceId, '/')[8])), \
+    PipelineName, RunId, Activity = '', Status, \
+    Error = tostring(column_ifexists('FailureType', ''))";
+
+const ACTIVITY_RUNS: &str = "ADFActivityRun \
+    | project TimeGenerated, Factory = tolower(tostring(split(_ResourceId, '/')[8])), \
+    PipelineName, RunId = PipelineRunId, Activity = ActivityName, Status, \
+    Error = tostring(column_ifexists('ErrorMessage', ''))";
+
+fn opsinsights_data_factory_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.factory.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.factory
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Factory".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.pipeline.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.pipeline
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("PipelineName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.run_id.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.run_id
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("RunId".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![PIPELINE_RUNS.to_owned(), ACTIVITY_RUNS.to_owned()]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn data_factory_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("PipelineName")).to_owned();
+    let unit = as_str_or_empty(row.get("RunId")).to_owned();
+    let status = as_str_or_empty(row.get("Status"));
+    let level = match status {
+        "Failed" => Level::Error,
+        "Cancelled" | "Cancelling" => Level::Warn,
+        _ => Level::Info,
+    };
+    let mut message = match as_str_or_empty(row.get("Activity")) {
+        "" => format!("pipeline {}", status),
+        activity => format!("{} {}", activity, status),
+    };
+    let error = as_str_or_empty(row.get("Error"));
+    if status == "Failed" && !error.is_empty() {
+        message.push_str(&format!(": {}", error.trim_end()));
+    }
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::DataFactory) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_data_factory_query(opts),
+        Box::new(data_factory_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{data_factory_row_to_entry, opsinsights_data_factory_query};
+    use crate::{
+        examples::adf_activity_run_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_failure_shows_error() {
+        let res = data_factory_row_to_entry(adf_activity_run_row());
+        assert_that(&res.group()).is_equal_to("copy-orders");
+        assert_that(&res.unit()).is_equal_to("8a3c2d1e-4f5b-4a6c-9d7e-0f1a2b3c4d5e");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.message()).is_equal_to(
+            "CopyOrdersToLake Failed: Failure happened on 'Sink' side. ErrorCode=UserErrorFailedFileOperation",
+        );
+    }
+
+    #[test]
+    pub fn row_to_entry_pipeline_transition() {
+        let mut row = adf_activity_run_row();
+        row.insert("Activity".to_owned(), json!(""));
+        row.insert("Status".to_owned(), json!("InProgress"));
+        let res = data_factory_row_to_entry(row);
+        assert_that(&res.level()).is_equal_to(Level::Info);
+        assert_that(&res.message()).is_equal_to("pipeline InProgress");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args =
+            opsinsights_base_args().chain(vec!["--factory", "aztail-adf", "--run-id", "ze-run"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_data_factory_query(&opts).to_string();
+        assert_that!(query).starts_with("union isfuzzy=true (ADFPipelineRun");
+        assert_that!(query).contains("Factory =~ 'aztail-adf'");
+        assert_that!(query).contains("RunId == 'ze-run'");
+    }
+}
@@ -0,0 +1,200 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    options::{Opts, Service},
+    source::{appinsight::AppInsights, opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, is_success, unwrap_as_rfc3339, unwrap_as_str};
+
+/// Dependencies are recorded by the calling app, so along with
+/// --dependency-type, --function-app and --function narrow them down to the
+/// calls made by those functions. On their own they select Functions only.
+fn appinsights_dependencies_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.dependency_type.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.dependency_type
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("type".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.function_app.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.function_app
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("cloud_RoleName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.function.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.function
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("operation_Name".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("dependencies".to_owned(), timespan, operators)
+}
+
+pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none() || !opts.requested_services().contains(&Service::Dependencies) {
+        return None;
+    }
+    Some(AppInsights::boxed(
+        appinsights_dependencies_query(opts),
+        Box::new(dependencies_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn dependencies_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
+    let group = unwrap_as_str(row.get("cloud_RoleName")).to_owned();
+    let unit = as_str_or_empty(row.get("operation_Name")).to_owned();
+    let level = if is_success(row.get("success")) {
+        Level::Info
+    } else {
+        Level::Warn
+    };
+    let message = format!(
+        "{} {} {} {} {}",
+        unwrap_as_str(row.get("type")),
+        as_str_or_empty(row.get("target")),
+        as_str_or_empty(row.get("name")),
+        as_str_or_empty(row.get("resultCode")),
+        row.get("duration").unwrap()
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn opsinsights_dependencies_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.dependency_type.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.dependency_type
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("DependencyType".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.function_app.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.function_app
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("AppRoleName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.function.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.function
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("OperationName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppDependencies".to_owned(), timespan, operators)
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Dependencies) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_dependencies_query(opts),
+        Box::new(appdependencies_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn appdependencies_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("AppRoleName")).to_owned();
+    let unit = as_str_or_empty(row.get("OperationName")).to_owned();
+    let level = if is_success(row.get("Success")) {
+        Level::Info
+    } else {
+        Level::Warn
+    };
+    let message = format!(
+        "{} {} {} {} {}",
+        unwrap_as_str(row.get("DependencyType")),
+        as_str_or_empty(row.get("Target")),
+        as_str_or_empty(row.get("Name")),
+        as_str_or_empty(row.get("ResultCode")),
+        row.get("DurationMs").unwrap()
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{appdependencies_row_to_entry, dependencies_row_to_entry};
+    use crate::{
+        examples::{appdependencies_row, dependencies_row},
+        options::cli_opts,
+        source::Level,
+        testing::{base_args, opsinsights_base_args},
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn dependencies_row_to_entry_marks_failures() {
+        let mut row = dependencies_row();
+        assert_that(&dependencies_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("success".to_owned(), json!("False"));
+        assert_that(&dependencies_row_to_entry(row).level()).is_equal_to(Level::Warn);
+    }
+
+    #[test]
+    fn dependencies_row_to_entry_message() {
+        let res = dependencies_row_to_entry(dependencies_row());
+        assert_that(&res.message()).is_equal_to("HTTP ifconfig.co GET /json 200 118.2236");
+    }
+
+    #[test]
+    fn appdependencies_row_to_entry_marks_failures() {
+        let res = appdependencies_row_to_entry(appdependencies_row());
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).starts_with("SQL aztail.database.windows.net | aztail ");
+    }
+
+    #[test]
+    fn queries_respect_dependency_type() {
+        let args = base_args().chain(vec!["--dependency-type", "SQL"]);
+        let query = super::appinsights_dependencies_query(&cli_opts(args).unwrap());
+        assert_that(&query.to_string()).contains("type == 'SQL'");
+        let args = opsinsights_base_args().chain(vec!["--dependency-type", "SQL"]);
+        let query = super::opsinsights_dependencies_query(&cli_opts(args).unwrap());
+        assert_that(&query.to_string()).contains("DependencyType == 'SQL'");
+    }
+}
@@ -0,0 +1,79 @@
+use serde_json::Value;
+
+use crate::source::Level;
+
+use super::{as_dynamic, as_str_or_empty};
+
+/// Category of the tracking events the Durable Task extension logs for each
+/// orchestrator, activity and entity state transition.
+const DURABLE_TASK_CATEGORY: &str = "Host.Triggers.DurableTask";
+
+/// Returns instance id, level and message for Durable Functions tracking
+/// traces, or None for ordinary traces.
+pub fn durable_transition(dimensions: Option<&Value>) -> Option<(String, Level, String)> {
+    let dimensions = as_dynamic(dimensions);
+    if as_str_or_empty(dimensions.get("Category")) != DURABLE_TASK_CATEGORY {
+        return None;
+    }
+    let instance_id = as_str_or_empty(dimensions.get("prop__instanceId"));
+    if instance_id.is_empty() {
+        return None;
+    }
+    let state = as_str_or_empty(dimensions.get("prop__state"));
+    let is_replay = match dimensions.get("prop__isReplay") {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => s.eq_ignore_ascii_case("true"),
+        _ => false,
+    };
+    let level = match state {
+        "Failed" => Level::Error,
+        "Terminated" => Level::Warn,
+        _ if is_replay => Level::Verbose,
+        _ => Level::Info,
+    };
+    let mut message = [
+        as_str_or_empty(dimensions.get("prop__functionType")),
+        as_str_or_empty(dimensions.get("prop__functionName")),
+        state,
+    ]
+    .iter()
+    .filter(|s| !s.is_empty())
+    .cloned()
+    .collect::<Vec<_>>()
+    .join(" ");
+    if is_replay {
+        message.push_str(" (replay)");
+    }
+    let reason = as_str_or_empty(dimensions.get("prop__reason"));
+    if !reason.is_empty() {
+        message.push_str(&format!(": {}", reason));
+    }
+    Some((instance_id.to_owned(), level, message))
+}
+
+#[cfg(test)]
+mod test {
+    use super::durable_transition;
+    use crate::examples::traces_durable_row;
+    use crate::source::Level;
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn transition_shows_function_type_name_and_state() {
+        let row = traces_durable_row();
+        let (unit, level, message) = durable_transition(row.get("customDimensions")).unwrap();
+        assert_that(&unit).is_equal_to("6f3b2a1c9d8e4f7a".to_owned());
+        assert_that(&level).is_equal_to(Level::Error);
+        assert_that(&message).is_equal_to(
+            "Activity SendInvoice Failed: System.TimeoutException: The operation has timed out."
+                .to_owned(),
+        );
+    }
+
+    #[test]
+    fn ordinary_traces_are_not_transitions() {
+        let dimensions = json!({"Category": "Function.ze-function.User"});
+        assert_that(&durable_transition(Some(&dimensions)).is_none()).is_true();
+    }
+}
@@ -0,0 +1,113 @@
+use serde_json::{Map, Value};
+
+use super::{as_dynamic, as_str_or_empty};
+
+/// Columns holding outer type, outer message, innermost type, innermost
+/// message and details, respectively.
+pub type ExceptionColumns = [&'static str; 5];
+
+pub const APPINSIGHTS_COLUMNS: ExceptionColumns = [
+    "outerType",
+    "outerMessage",
+    "innermostType",
+    "innermostMessage",
+    "details",
+];
+
+pub const OPSINSIGHTS_COLUMNS: ExceptionColumns = [
+    "OuterType",
+    "OuterMessage",
+    "InnermostType",
+    "InnermostMessage",
+    "Details",
+];
+
+pub fn exception_message(
+    row: &Map<String, Value>,
+    [outer_type, outer_message, innermost_type, innermost_message, details]: ExceptionColumns,
+    collapse_stack_trace: bool,
+) -> String {
+    let outer = format!(
+        "{}: {}",
+        as_str_or_empty(row.get(outer_type)),
+        as_str_or_empty(row.get(outer_message))
+    );
+    let innermost = format!(
+        "{}: {}",
+        as_str_or_empty(row.get(innermost_type)),
+        as_str_or_empty(row.get(innermost_message))
+    );
+    let mut message = if innermost == outer || innermost == ": " {
+        outer
+    } else {
+        format!("{} ---> {}", outer, innermost)
+    };
+    if !collapse_stack_trace {
+        for line in stack_trace_lines(row.get(details)) {
+            message.push('\n');
+            message.push_str(&line);
+        }
+    }
+    message
+}
+
+/// Details are the chain of exceptions starting with the outer one.
+fn stack_trace_lines(details: Option<&Value>) -> Vec<String> {
+    let details = as_dynamic(details);
+    let mut lines = Vec::new();
+    for (n, detail) in details.as_array().into_iter().flatten().enumerate() {
+        if n > 0 {
+            lines.push(format!(
+                "  ---> {}: {}",
+                as_str_or_empty(detail.get("type")),
+                as_str_or_empty(detail.get("message"))
+            ));
+        }
+        match detail.get("parsedStack").and_then(Value::as_array) {
+            Some(frames) => lines.extend(frames.iter().map(stack_frame_line)),
+            None => lines.extend(
+                as_str_or_empty(detail.get("rawStack"))
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(|l| format!("    {}", l.trim())),
+            ),
+        }
+    }
+    lines
+}
+
+fn stack_frame_line(frame: &Value) -> String {
+    let method = as_str_or_empty(frame.get("method"));
+    match as_str_or_empty(frame.get("fileName")) {
+        "" => format!("    at {}", method),
+        file_name => format!(
+            "    at {} in {}:line {}",
+            method,
+            file_name,
+            frame.get("line").and_then(Value::as_i64).unwrap_or(0)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{exception_message, APPINSIGHTS_COLUMNS};
+    use crate::examples::exceptions_functions_row;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn message_shows_outer_and_innermost_exception() {
+        let res = exception_message(&exceptions_functions_row(), APPINSIGHTS_COLUMNS, true);
+        assert_that(&res).is_equal_to(
+            "Microsoft.Azure.WebJobs.Host.FunctionInvocationException: Exception while executing function: Functions.log-function ---> System.InvalidOperationException: ze-failure".to_owned(),
+        );
+    }
+
+    #[test]
+    fn message_expands_stack_trace() {
+        let res = exception_message(&exceptions_functions_row(), APPINSIGHTS_COLUMNS, false);
+        assert_that(&res.lines().count()).is_equal_to(4);
+        assert_that(&res).contains("\n  ---> System.InvalidOperationException: ze-failure\n");
+        assert_that(&res).contains("    at Function.Run in /home/site/wwwroot/run.csx:line 12");
+    }
+}
@@ -1,15 +1,57 @@
 use serde_json::{Map, Value};
 
 use crate::{
-    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    kusto::{Contains, Eq, Extend, Filter, Not, Operator, Or, Ordering, Query, Timespan},
     options::{Opts, Service},
     source::{appinsight::AppInsights, opsinsight::OpsLogs, Level, LogEntry, LogSource},
 };
 
+use super::apim::APIM_ROLE_MARKER;
+use super::durable::durable_transition;
+use super::exceptions::{exception_message, APPINSIGHTS_COLUMNS, OPSINSIGHTS_COLUMNS};
 use super::{unwrap_as_rfc3339, unwrap_as_str};
 
 fn appinsights_functions_query(opts: &Opts) -> Query {
     let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = appinsights_functions_filters(opts);
+    operators.extend(orchestration_filters(opts, "customDimensions"));
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("traces".to_owned(), timespan, operators)
+}
+
+/// Only Durable Functions traces carry the instance id, so the exception
+/// sources are left out altogether when filtering on it.
+fn orchestration_filters(opts: &Opts, dimensions: &str) -> Vec<Box<dyn Operator>> {
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.orchestration_id.is_empty() {
+        operators.push(Extend::boxed(
+            "InstanceId".to_owned(),
+            format!("tostring({}.prop__instanceId)", dimensions),
+        ));
+        operators.push(Filter::boxed(Or::new(
+            opts.orchestration_id
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("InstanceId".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators
+}
+
+fn appinsights_exceptions_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("timestamp".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Not::new(Contains::boxed(
+        "cloud_RoleName".to_owned(),
+        APIM_ROLE_MARKER.to_owned(),
+    ))));
+    operators.extend(appinsights_functions_filters(opts));
+    operators.push(Ordering::boxed("timestamp".to_owned()));
+    Query::new("exceptions".to_owned(), timespan, operators)
+}
+
+fn appinsights_functions_filters(opts: &Opts) -> Vec<Box<dyn Operator>> {
     let mut operators: Vec<Box<dyn Operator>> = Vec::new();
     if !opts.function_app.is_empty() {
         operators.push(Filter::boxed(Or::new(
@@ -29,8 +71,7 @@ fn appinsights_functions_query(opts: &Opts) -> Query {
                 .collect(),
         )));
     }
-    operators.push(Ordering::boxed("timestamp".to_owned()));
-    Query::new("traces".to_owned(), timespan, operators)
+    operators
 }
 
 pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
@@ -44,9 +85,34 @@ pub fn appinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     ))
 }
 
+pub fn appinsights_exceptions(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.app_id.is_none()
+        || !opts.requested_services().contains(&Service::Functions)
+        || !opts.orchestration_id.is_empty()
+    {
+        return None;
+    }
+    let collapse = opts.collapse_stack_traces;
+    Some(AppInsights::boxed(
+        appinsights_exceptions_query(opts),
+        Box::new(move |row| exceptions_row_to_entry(row, collapse)),
+        opts.clone(),
+    ))
+}
+
 pub fn traces_row_to_entry(row: Map<String, Value>) -> LogEntry {
     let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
     let group = unwrap_as_str(row.get("cloud_RoleName")).to_owned();
+    if let Some((unit, level, message)) = durable_transition(row.get("customDimensions")) {
+        return LogEntry {
+            timestamp,
+            group,
+            unit,
+            level,
+            message,
+            raw: row,
+        };
+    }
     let unit = unwrap_as_str(row.get("operation_Name")).to_owned();
     let level = match row.get("severityLevel").unwrap().as_i64() {
         Some(3) => Level::Error,
@@ -65,8 +131,42 @@ pub fn traces_row_to_entry(row: Map<String, Value>) -> LogEntry {
     }
 }
 
+fn exceptions_row_to_entry(row: Map<String, Value>, collapse_stack_trace: bool) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("timestamp"));
+    let group = unwrap_as_str(row.get("cloud_RoleName")).to_owned();
+    let unit = unwrap_as_str(row.get("operation_Name")).to_owned();
+    let message = exception_message(&row, APPINSIGHTS_COLUMNS, collapse_stack_trace);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Error,
+        message,
+        raw: row,
+    }
+}
+
 fn opsinsights_functions_query(opts: &Opts) -> Query {
     let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = opsinsights_functions_filters(opts);
+    operators.extend(orchestration_filters(opts, "Properties"));
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppTraces".to_owned(), timespan, operators)
+}
+
+fn opsinsights_exceptions_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Not::new(Contains::boxed(
+        "AppRoleName".to_owned(),
+        APIM_ROLE_MARKER.to_owned(),
+    ))));
+    operators.extend(opsinsights_functions_filters(opts));
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AppExceptions".to_owned(), timespan, operators)
+}
+
+fn opsinsights_functions_filters(opts: &Opts) -> Vec<Box<dyn Operator>> {
     let mut operators: Vec<Box<dyn Operator>> = Vec::new();
     if !opts.function_app.is_empty() {
         operators.push(Filter::boxed(Or::new(
@@ -86,8 +186,7 @@ fn opsinsights_functions_query(opts: &Opts) -> Query {
                 .collect(),
         )));
     }
-    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
-    Query::new("AppTraces".to_owned(), timespan, operators)
+    operators
 }
 
 pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
@@ -101,9 +200,34 @@ pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>>
     ))
 }
 
+pub fn opsinsights_exceptions(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || !opts.requested_services().contains(&Service::Functions)
+        || !opts.orchestration_id.is_empty()
+    {
+        return None;
+    }
+    let collapse = opts.collapse_stack_traces;
+    Some(OpsLogs::boxed(
+        opsinsights_exceptions_query(opts),
+        Box::new(move |row| appexceptions_row_to_entry(row, collapse)),
+        opts.clone(),
+    ))
+}
+
 fn apptraces_row_to_entry(row: Map<String, Value>) -> LogEntry {
     let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
     let group = unwrap_as_str(row.get("AppRoleName")).to_owned();
+    if let Some((unit, level, message)) = durable_transition(row.get("Properties")) {
+        return LogEntry {
+            timestamp,
+            group,
+            unit,
+            level,
+            message,
+            raw: row,
+        };
+    }
     let unit = unwrap_as_str(row.get("OperationName")).to_owned();
     let level = match row.get("SeverityLevel").unwrap().as_i64() {
         Some(3) => Level::Error,
@@ -122,11 +246,29 @@ fn apptraces_row_to_entry(row: Map<String, Value>) -> LogEntry {
     }
 }
 
+fn appexceptions_row_to_entry(row: Map<String, Value>, collapse_stack_trace: bool) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("AppRoleName")).to_owned();
+    let unit = unwrap_as_str(row.get("OperationName")).to_owned();
+    let message = exception_message(&row, OPSINSIGHTS_COLUMNS, collapse_stack_trace);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level: Level::Error,
+        message,
+        raw: row,
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::traces_row_to_entry;
+    use super::{exceptions_row_to_entry, traces_row_to_entry};
     use crate::{
-        examples::traces_functions_row, options::cli_opts, source::Level, testing::base_args,
+        examples::{exceptions_functions_row, traces_durable_row, traces_functions_row},
+        options::cli_opts,
+        source::Level,
+        testing::{base_args, opsinsights_base_args},
     };
     use speculoos::prelude::*;
 
@@ -152,4 +294,51 @@ mod test {
         let query = super::opsinsights_functions_query(&opts);
         assert_that(&query.to_string()).contains("ze-func");
     }
+
+    #[test]
+    fn exceptions_row_to_entry_is_error() {
+        let res = exceptions_row_to_entry(exceptions_functions_row(), false);
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.unit()).is_equal_to("ze-operation");
+    }
+
+    #[test]
+    fn appinsights_exceptions_excludes_apim() {
+        let opts = cli_opts(base_args()).unwrap();
+        let query = super::appinsights_exceptions_query(&opts);
+        assert_that(&query.to_string())
+            .contains("not(cloud_RoleName contains_cs '.azure-api.net')");
+    }
+
+    #[test]
+    fn traces_row_to_entry_durable_instance_is_unit() {
+        let res = traces_row_to_entry(traces_durable_row());
+        assert_that(&res.group()).is_equal_to("aztail-func");
+        assert_that(&res.unit()).is_equal_to("6f3b2a1c9d8e4f7a");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    fn orchestration_id_filters_on_instance_id() {
+        let args = base_args().chain(vec!["--orchestration-id", "6f3b2a1c9d8e4f7a"]);
+        let opts = cli_opts(args).unwrap();
+        let query = super::appinsights_functions_query(&opts);
+        assert_that(&query.to_string()).contains("InstanceId == '6f3b2a1c9d8e4f7a'");
+        let args = opsinsights_base_args().chain(vec!["--orchestration-id", "6f3b2a1c9d8e4f7a"]);
+        let opts = cli_opts(args).unwrap();
+        let query = super::opsinsights_functions_query(&opts);
+        assert_that(&query.to_string()).contains("InstanceId == '6f3b2a1c9d8e4f7a'");
+    }
+
+    #[test]
+    fn orchestration_id_shows_only_traces() {
+        let args = base_args().chain(vec!["--orchestration-id", "6f3b2a1c9d8e4f7a"]);
+        let opts = cli_opts(args).unwrap();
+        assert_that!(super::appinsights(&opts).into_iter().count()).is_equal_to(1);
+        assert_that!(super::appinsights_exceptions(&opts).into_iter().count()).is_equal_to(0);
+        let args = opsinsights_base_args().chain(vec!["--orchestration-id", "6f3b2a1c9d8e4f7a"]);
+        let opts = cli_opts(args).unwrap();
+        assert_that!(super::opsinsights(&opts).into_iter().count()).is_equal_to(1);
+        assert_that!(super::opsinsights_exceptions(&opts).into_iter().count()).is_equal_to(0);
+    }
 }
@@ -0,0 +1,208 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Extend, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+const SIGNIN_LOGS: &str = "SigninLogs \
+    | project TimeGenerated, Principal = UserPrincipalName, AppDisplayName, \
+    ResourceDisplayName, ResultType = tostring(ResultType), ResultDescription, IPAddress";
+
+/// Managed identities and service principals sign in on their own behalf,
+/// so the principal doubles as the app.
+const MANAGED_IDENTITY_SIGNIN_LOGS: &str = "AADManagedIdentitySignInLogs \
+    | project TimeGenerated, Principal = ServicePrincipalName, \
+    AppDisplayName = ServicePrincipalName, ResourceDisplayName, \
+    ResultType = tostring(ResultType), ResultDescription, IPAddress";
+
+const SERVICE_PRINCIPAL_SIGNIN_LOGS: &str = "AADServicePrincipalSignInLogs \
+    | project TimeGenerated, Principal = ServicePrincipalName, \
+    AppDisplayName = ServicePrincipalName, ResourceDisplayName, \
+    ResultType = tostring(ResultType), ResultDescription, IPAddress";
+
+fn identity_filters(opts: &Opts) -> Vec<Box<dyn Operator>> {
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.user_principal.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.user_principal
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Principal".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.app_display_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.app_display_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("AppDisplayName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators
+}
+
+fn opsinsights_signin_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators = identity_filters(opts);
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![
+        SIGNIN_LOGS.to_owned(),
+        MANAGED_IDENTITY_SIGNIN_LOGS.to_owned(),
+        SERVICE_PRINCIPAL_SIGNIN_LOGS.to_owned(),
+    ]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn signin_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("AppDisplayName")).to_owned();
+    let unit = as_str_or_empty(row.get("Principal")).to_owned();
+    let result_type = as_str_or_empty(row.get("ResultType"));
+    let mut message = format!(
+        "{} sign-in to {}",
+        as_str_or_empty(row.get("IPAddress")),
+        as_str_or_empty(row.get("ResourceDisplayName"))
+    );
+    let level = match result_type {
+        "" | "0" => Level::Info,
+        _ => {
+            message.push_str(&format!(
+                " failed: {} {}",
+                result_type,
+                as_str_or_empty(row.get("ResultDescription"))
+            ));
+            Level::Error
+        }
+    };
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message: message.trim_end().to_owned(),
+        raw: row,
+    }
+}
+
+pub fn opsinsights_signin(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Identity) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_signin_query(opts),
+        Box::new(signin_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn opsinsights_audit_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    for (field, expression) in [
+        (
+            "Principal",
+            "coalesce(tostring(InitiatedBy.user.userPrincipalName), \
+            tostring(InitiatedBy.app.servicePrincipalName))",
+        ),
+        ("AppDisplayName", "tostring(InitiatedBy.app.displayName)"),
+        ("Target", "tostring(TargetResources[0].displayName)"),
+    ] {
+        operators.push(Extend::boxed(field.to_owned(), expression.to_owned()));
+    }
+    operators.extend(identity_filters(opts));
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AuditLogs".to_owned(), timespan, operators)
+}
+
+fn audit_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("LoggedByService")).to_owned();
+    let unit = as_str_or_empty(row.get("Principal")).to_owned();
+    let result = as_str_or_empty(row.get("Result"));
+    let mut message = [
+        as_str_or_empty(row.get("OperationName")),
+        as_str_or_empty(row.get("Target")),
+        result,
+    ]
+    .iter()
+    .filter(|s| !s.is_empty())
+    .cloned()
+    .collect::<Vec<_>>()
+    .join(" ");
+    let level = if result == "failure" {
+        message.push_str(&format!(": {}", as_str_or_empty(row.get("ResultReason"))));
+        Level::Error
+    } else {
+        Level::Info
+    };
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights_audit(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Identity) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_audit_query(opts),
+        Box::new(audit_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{audit_row_to_entry, opsinsights_signin_query, signin_row_to_entry};
+    use crate::{
+        examples::{audit_logs_row, managed_identity_signin_row},
+        options::cli_opts,
+        source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn signin_row_to_entry_failure_is_error_with_reason() {
+        let res = signin_row_to_entry(managed_identity_signin_row());
+        assert_that(&res.group()).is_equal_to("aztail-func");
+        assert_that(&res.unit()).is_equal_to("aztail-func");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.message()).is_equal_to(
+            "20.50.2.17 sign-in to Azure Key Vault failed: 700016 Application with identifier was not found in the directory.",
+        );
+    }
+
+    #[test]
+    pub fn audit_row_to_entry_success_is_info() {
+        let mut row = audit_logs_row();
+        let res = audit_row_to_entry(row.clone());
+        assert_that(&res.group()).is_equal_to("Core Directory");
+        assert_that(&res.unit()).is_equal_to("alice@example.com");
+        assert_that(&res.level()).is_equal_to(Level::Info);
+        assert_that(&res.message()).is_equal_to("Add member to group aztail-operators success");
+        row.insert("Result".to_owned(), json!("failure"));
+        assert_that(&audit_row_to_entry(row).level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--user-principal", "alice@example.com"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_signin_query(&opts).to_string();
+        assert_that!(query).contains("(AADManagedIdentitySignInLogs | project");
+        assert_that!(query).contains("Principal =~ 'alice@example.com'");
+    }
+}
@@ -0,0 +1,142 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Extend, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+/// Columns identifying the caller, in order of preference. Managed
+/// identities and service principals have no UPN, only an app id.
+const CALLER_COLUMNS: [&str; 4] = [
+    "identity_claim_upn_s",
+    "identity_claim_appid_g",
+    "identity_claim_http_schemas_microsoft_com_identity_claims_objectidentifier_g",
+    "CallerIPAddress",
+];
+
+/// Secret, key and certificate names are the fifth segment of id_s, which
+/// looks like https://{vault}.vault.azure.net/secrets/{name}/{version}.
+fn opsinsights_key_vault_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Eq::new(
+        "ResourceProvider".to_owned(),
+        "MICROSOFT.KEYVAULT".to_owned(),
+    )));
+    operators.push(Filter::boxed(Eq::new(
+        "Category".to_owned(),
+        "AuditEvent".to_owned(),
+    )));
+    operators.push(Extend::boxed(
+        "SecretName".to_owned(),
+        "tostring(split(id_s, '/')[4])".to_owned(),
+    ));
+    if !opts.vault.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.vault
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Resource".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.secret_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.secret_name
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("SecretName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AzureDiagnostics".to_owned(), timespan, operators)
+}
+
+fn key_vault_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("Resource")).to_lowercase();
+    let unit = as_str_or_empty(row.get("SecretName")).to_owned();
+    let status_code = row.get("httpStatusCode_d").and_then(Value::as_f64);
+    let level = match status_code.map(|s| s as i64) {
+        Some(401) | Some(403) => Level::Warn,
+        Some(status) if status >= 500 => Level::Error,
+        _ => Level::Info,
+    };
+    let caller = CALLER_COLUMNS
+        .iter()
+        .map(|c| as_str_or_empty(row.get(*c)))
+        .find(|c| !c.is_empty())
+        .unwrap_or("-");
+    let message = format!(
+        "{} by {} {} {}",
+        as_str_or_empty(row.get("OperationName")),
+        caller,
+        as_str_or_empty(row.get("ResultSignature")),
+        status_code.map_or("-".to_owned(), |s| (s as i64).to_string())
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::KeyVault) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_key_vault_query(opts),
+        Box::new(key_vault_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{key_vault_row_to_entry, opsinsights_key_vault_query};
+    use crate::{
+        examples::key_vault_row, options::cli_opts, source::Level, testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_forbidden_is_warn() {
+        let res = key_vault_row_to_entry(key_vault_row());
+        assert_that(&res.group()).is_equal_to("aztail-kv");
+        assert_that(&res.unit()).is_equal_to("db-password");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message())
+            .is_equal_to("SecretGet by 9c1d7e4b-2a3f-4e5d-8b6c-7a8f9e0d1c2b Forbidden 403");
+    }
+
+    #[test]
+    pub fn row_to_entry_success_is_info() {
+        let mut row = key_vault_row();
+        row.insert("httpStatusCode_d".to_owned(), json!(200.0));
+        row.insert(
+            "identity_claim_upn_s".to_owned(),
+            json!("alice@example.com"),
+        );
+        let res = key_vault_row_to_entry(row);
+        assert_that(&res.level()).is_equal_to(Level::Info);
+        assert_that(&res.message()).starts_with("SecretGet by alice@example.com ");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--vault", "aztail-kv"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_key_vault_query(&opts).to_string();
+        assert_that!(query).contains("ResourceProvider == 'MICROSOFT.KEYVAULT'");
+        assert_that!(query).contains("Resource =~ 'aztail-kv'");
+    }
+}
@@ -0,0 +1,393 @@
+use chrono::Duration;
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, Extend, Filter, Not, Operator, Or, Ordering, Query, StartsWith, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+const CONTAINER_LOG_V2: &str = "ContainerLogV2 \
+    | project TimeGenerated, Computer, PodNamespace, PodName, ContainerName, \
+    LogSource, LogMessage = tostring(LogMessage)";
+
+/// The legacy ContainerLog table knows only the container ID, so pod and
+/// namespace are looked up in KubePodInventory. The inventory is recorded
+/// every few minutes for running pods, so only the queried time range and
+/// an hour either side of it is scanned, rather than its whole retention.
+/// The range moves along with the query when following.
+fn container_log(timespan: &Timespan) -> String {
+    format!(
+        "ContainerLog \
+        | join kind=inner (KubePodInventory{} \
+        | summarize by ContainerID, PodNamespace = Namespace, PodName = Name, \
+        ContainerName = tostring(split(ContainerName, '/')[1])) on ContainerID \
+        | project TimeGenerated, Computer, PodNamespace, PodName, ContainerName, \
+        LogSource = LogEntrySource, LogMessage = LogEntry",
+        timespan.widened(Duration::hours(1))
+    )
+}
+
+fn container_log_tables(timespan: &Timespan) -> String {
+    Union::new(vec![CONTAINER_LOG_V2.to_owned(), container_log(timespan)]).to_string()
+}
+
+fn opsinsights_kubernetes_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.namespace.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.namespace
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("PodNamespace".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.pod.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.pod
+                .iter()
+                .cloned()
+                .map(|n| StartsWith::boxed("PodName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.container.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.container
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("ContainerName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::timed(container_log_tables, timespan, operators)
+}
+
+fn kubernetes_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = format!(
+        "{}/{}",
+        unwrap_as_str(row.get("PodNamespace")),
+        unwrap_as_str(row.get("PodName"))
+    );
+    let unit = unwrap_as_str(row.get("ContainerName")).to_owned();
+    let level = match as_str_or_empty(row.get("LogSource")) {
+        "stderr" => Level::Error,
+        _ => Level::Info,
+    };
+    let message = as_str_or_empty(row.get("LogMessage")).trim_end().to_owned();
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || !opts.verb.is_empty()
+        || !opts.requested_services().contains(&Service::Kubernetes)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_kubernetes_query(opts),
+        Box::new(kubernetes_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+/// Events are grouped like container logs so that e.g. BackOff events are
+/// interleaved with the output of the crashing pod.
+fn opsinsights_events_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.namespace.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.namespace
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Namespace".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.pod.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.pod
+                .iter()
+                .cloned()
+                .map(|n| StartsWith::boxed("Name".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("KubeEvents".to_owned(), timespan, operators)
+}
+
+fn events_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = format!(
+        "{}/{}",
+        as_str_or_empty(row.get("Namespace")),
+        as_str_or_empty(row.get("Name"))
+    );
+    let unit = as_str_or_empty(row.get("SourceComponent")).to_owned();
+    let level = match as_str_or_empty(row.get("KubeEventType")) {
+        "Warning" => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = format!(
+        "{}: {}",
+        as_str_or_empty(row.get("Reason")),
+        as_str_or_empty(row.get("Message")).trim_end()
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights_events(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || !opts.verb.is_empty()
+        || !opts.requested_services().contains(&Service::Kubernetes)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_events_query(opts),
+        Box::new(events_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+/// Controllers continuously get, list and watch the API server, so read-only
+/// requests are left out of the audit log unless asked for with --verb.
+const READ_ONLY_VERBS: [&str; 3] = ["get", "list", "watch"];
+
+fn opsinsights_audit_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    for (field, expression) in [
+        ("Namespace", "tostring(ObjectRef.namespace)"),
+        ("ObjectName", "tostring(ObjectRef.name)"),
+        ("ObjectResource", "tostring(ObjectRef.resource)"),
+        ("Username", "tostring(User.username)"),
+        ("StatusCode", "toint(ResponseStatus.code)"),
+    ] {
+        operators.push(Extend::boxed(field.to_owned(), expression.to_owned()));
+    }
+    if opts.verb.is_empty() {
+        operators.push(Filter::boxed(Not::new(Box::new(Or::new(
+            READ_ONLY_VERBS
+                .iter()
+                .map(|v| Eq::boxed("Verb".to_owned(), (*v).to_owned()))
+                .collect(),
+        )))));
+    } else {
+        operators.push(Filter::boxed(Or::new(
+            opts.verb
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Verb".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.namespace.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.namespace
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Namespace".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.pod.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.pod
+                .iter()
+                .cloned()
+                .map(|n| StartsWith::boxed("ObjectName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AKSAudit".to_owned(), timespan, operators)
+}
+
+fn audit_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = match as_str_or_empty(row.get("Namespace")) {
+        "" => as_str_or_empty(row.get("ObjectName")).to_owned(),
+        namespace => format!("{}/{}", namespace, as_str_or_empty(row.get("ObjectName"))),
+    };
+    let unit = as_str_or_empty(row.get("ObjectResource")).to_owned();
+    let status_code = row.get("StatusCode").and_then(Value::as_i64);
+    let level = match status_code {
+        Some(status) if status >= 400 => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = format!(
+        "{} {} {} {}",
+        as_str_or_empty(row.get("Username")),
+        as_str_or_empty(row.get("Verb")),
+        as_str_or_empty(row.get("RequestUri")),
+        status_code.map_or("-".to_owned(), |s| s.to_string())
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights_audit(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || !opts.container.is_empty()
+        || !opts.requested_services().contains(&Service::Kubernetes)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_audit_query(opts),
+        Box::new(audit_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+fn opsinsights_control_plane_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let operators: Vec<Box<dyn Operator>> = vec![Ordering::boxed("TimeGenerated".to_owned())];
+    Query::new("AKSControlPlane".to_owned(), timespan, operators)
+}
+
+fn control_plane_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("Category")).to_owned();
+    let unit = as_str_or_empty(row.get("PodName")).to_owned();
+    let level = match as_str_or_empty(row.get("Level")).to_uppercase().as_str() {
+        "ERROR" | "FATAL" => Level::Error,
+        "WARNING" => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = as_str_or_empty(row.get("Message")).trim_end().to_owned();
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+/// Control plane logs belong to the cluster rather than to a namespace or
+/// pod, so they are only shown when no Kubernetes filters are used.
+pub fn opsinsights_control_plane(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none()
+        || [&opts.namespace, &opts.pod, &opts.container, &opts.verb]
+            .iter()
+            .any(|filter| !filter.is_empty())
+        || !opts.requested_services().contains(&Service::Kubernetes)
+    {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_control_plane_query(opts),
+        Box::new(control_plane_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        audit_row_to_entry, events_row_to_entry, kubernetes_row_to_entry, opsinsights,
+        opsinsights_audit_query, opsinsights_control_plane, opsinsights_kubernetes_query,
+    };
+    use crate::{
+        examples::{aks_audit_row, container_log_v2_row, kube_events_row},
+        options::cli_opts,
+        source::Level,
+        testing::opsinsights_base_args,
+    };
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_group_and_unit() {
+        let res = kubernetes_row_to_entry(container_log_v2_row());
+        assert_that(&res.group()).is_equal_to("default/aztail-api-7d9c8b6f4-x2x9l");
+        assert_that(&res.unit()).is_equal_to("api");
+        assert_that(&res.level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--namespace", "default", "--pod", "ze-pod"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_kubernetes_query(&opts);
+        assert_that!(query.to_string()).starts_with("union isfuzzy=true (ContainerLogV2");
+        assert_that!(query.to_string()).contains("PodNamespace == 'default'");
+        assert_that!(query.to_string()).contains("PodName startswith_cs 'ze-pod'");
+        assert_that!(query.to_string()).contains(
+            "(KubePodInventory | where TimeGenerated > datetime(2021-10-31T22:50:00+00:00) |",
+        );
+    }
+
+    #[test]
+    pub fn pod_inventory_lookup_advances_with_query() {
+        let opts = cli_opts(opsinsights_base_args().chain(vec!["--follow"])).unwrap();
+        let mut query = opsinsights_kubernetes_query(&opts);
+        query.advance_start("2021-11-01T02:15:00+00:00".parse().ok());
+        assert_that!(query.to_string()).contains(
+            "(KubePodInventory | where TimeGenerated > datetime(2021-11-01T01:15:00+00:00) |",
+        );
+    }
+
+    #[test]
+    pub fn events_row_to_entry_warning_is_warn() {
+        let res = events_row_to_entry(kube_events_row());
+        assert_that(&res.group()).is_equal_to("default/aztail-api-7d9c8b6f4-x2x9l");
+        assert_that(&res.unit()).is_equal_to("kubelet");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message())
+            .is_equal_to("BackOff: Back-off restarting failed container api in pod aztail-api-7d9c8b6f4-x2x9l_default(3f1c2e9a-5b1d-4c7e-9a0b-2d6e8f4a1c3b)");
+    }
+
+    #[test]
+    pub fn audit_row_to_entry_names_user_and_verb() {
+        let res = audit_row_to_entry(aks_audit_row());
+        assert_that(&res.group()).is_equal_to("default/aztail-api");
+        assert_that(&res.unit()).is_equal_to("deployments");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to(
+            "alice@example.com patch /apis/apps/v1/namespaces/default/deployments/aztail-api 403",
+        );
+    }
+
+    #[test]
+    pub fn verb_selects_audit_log() {
+        let args = opsinsights_base_args().chain(vec!["--verb", "delete"]);
+        let opts = cli_opts(args).unwrap();
+        assert_that(&opsinsights(&opts).into_iter().count()).is_equal_to(0);
+        assert_that(&opsinsights_control_plane(&opts).into_iter().count()).is_equal_to(0);
+        let query = opsinsights_audit_query(&opts).to_string();
+        assert_that!(query).contains("Verb == 'delete'");
+        assert_that!(query).does_not_contain("'watch'");
+    }
+}
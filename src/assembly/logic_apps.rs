@@ -0,0 +1,129 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+fn opsinsights_logic_apps_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    operators.push(Filter::boxed(Eq::new(
+        "Category".to_owned(),
+        "WorkflowRuntime".to_owned(),
+    )));
+    if !opts.workflow.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.workflow
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("resource_workflowName_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.workflow_run.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.workflow_run
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("resource_runId_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AzureDiagnostics".to_owned(), timespan, operators)
+}
+
+fn logic_apps_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("resource_workflowName_s")).to_owned();
+    let unit = unwrap_as_str(row.get("resource_runId_s")).to_owned();
+    let level = match as_str_or_empty(row.get("status_s")) {
+        "Failed" | "TimedOut" | "Aborted" => Level::Error,
+        "Cancelled" => Level::Warn,
+        "Skipped" => Level::Verbose,
+        _ => Level::Info,
+    };
+    let message = logic_apps_message_line(&row);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn logic_apps_message_line(row: &Map<String, Value>) -> String {
+    let operation = unwrap_as_str(row.get("OperationName"))
+        .rsplit('/')
+        .next()
+        .unwrap();
+    let step = match as_str_or_empty(row.get("resource_actionName_s")) {
+        "" => as_str_or_empty(row.get("resource_triggerName_s")),
+        action => action,
+    };
+    let status = as_str_or_empty(row.get("status_s"));
+    let mut message = [operation, step, status]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let error_code = as_str_or_empty(row.get("error_code_s"));
+    if !error_code.is_empty() {
+        message.push_str(&format!(
+            ": {} {}",
+            error_code,
+            as_str_or_empty(row.get("error_message_s"))
+        ));
+    }
+    message
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::LogicApps) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_logic_apps_query(opts),
+        Box::new(logic_apps_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{logic_apps_row_to_entry, opsinsights_logic_apps_query};
+    use crate::{
+        examples::logic_apps_row, options::cli_opts, source::Level, testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_sets_level() {
+        let mut row = logic_apps_row();
+        assert_that(&logic_apps_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("status_s".to_owned(), json!("Failed"));
+        assert_that(&logic_apps_row_to_entry(row).level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    pub fn row_to_entry_message_names_action_and_status() {
+        let res = logic_apps_row_to_entry(logic_apps_row());
+        assert_that(&res.message()).is_equal_to("workflowActionCompleted ifconfig Succeeded");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--workflow", "ze-workflow"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_logic_apps_query(&opts);
+        assert_that!(query.to_string()).contains("resource_workflowName_s == 'ze-workflow'");
+    }
+}
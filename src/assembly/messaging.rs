@@ -0,0 +1,161 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+/// Each table is projected onto the same columns. Columns that differ
+/// between Service Bus and Event Hubs or between schema versions are read
+/// with column_ifexists, since a missing column would make the union
+/// silently drop the whole table.
+const OPERATIONAL_LOGS: &str = "AZMSOperationalLogs \
+    | project TimeGenerated, Namespace = tolower(tostring(split(_ResourceId, '/')[8])), \
+    Entity = tostring(column_ifexists('EntityName', '')), Activity = EventName, \
+    Status = tostring(column_ifexists('Status', '')), \
+    Detail = tostring(column_ifexists('Caller', '')), Count = long(null)";
+
+const RUNTIME_AUDIT_LOGS: &str = "AZMSRuntimeAuditLogs \
+    | project TimeGenerated, Namespace = tolower(tostring(split(_ResourceId, '/')[8])), \
+    Entity = tostring(column_ifexists('EntityName', '')), Activity = ActivityName, \
+    Status = tostring(column_ifexists('Status', '')), \
+    Detail = strcat(column_ifexists('AuthType', ''), ' ', column_ifexists('ClientIp', '')), \
+    Count = tolong(column_ifexists('Count', long(null)))";
+
+const APPLICATION_METRIC_LOGS: &str = "AZMSApplicationMetricLogs \
+    | project TimeGenerated, Namespace = tolower(tostring(split(_ResourceId, '/')[8])), \
+    Entity = tostring(column_ifexists('EntityName', '')), Activity = ActivityName, \
+    Status = '', Detail = tostring(column_ifexists('ChildEntityName', '')), \
+    Count = tolong(column_ifexists('Count', long(null)))";
+
+/// Namespaces created before the resource-specific tables write their
+/// operational logs to AzureDiagnostics.
+const AZURE_DIAGNOSTICS: &str = "AzureDiagnostics \
+    | where ResourceProvider in ('MICROSOFT.SERVICEBUS', 'MICROSOFT.EVENTHUB') \
+    | project TimeGenerated, Namespace = tolower(Resource), \
+    Entity = tostring(column_ifexists('EntityName_s', '')), \
+    Activity = tostring(column_ifexists('EventName_s', OperationName)), \
+    Status = tostring(column_ifexists('Status_s', '')), \
+    Detail = tostring(column_ifexists('Caller_s', '')), Count = long(null)";
+
+fn opsinsights_messaging_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.namespace_name.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.namespace_name
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("Namespace".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.entity.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.entity
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Entity".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![
+        OPERATIONAL_LOGS.to_owned(),
+        RUNTIME_AUDIT_LOGS.to_owned(),
+        APPLICATION_METRIC_LOGS.to_owned(),
+        AZURE_DIAGNOSTICS.to_owned(),
+    ]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn messaging_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("Namespace")).to_owned();
+    let unit = as_str_or_empty(row.get("Entity")).to_owned();
+    let activity = as_str_or_empty(row.get("Activity"));
+    let status = as_str_or_empty(row.get("Status"));
+    let detail = as_str_or_empty(row.get("Detail")).trim();
+    let level = messaging_level(&[activity, status, detail].join(" ").to_lowercase());
+    let mut message = [activity, status, detail]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some(count) = row.get("Count").and_then(Value::as_i64) {
+        message.push_str(&format!(" (count {})", count));
+    }
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn messaging_level(text: &str) -> Level {
+    if text.contains("fail") {
+        Level::Error
+    } else if text.contains("deadletter") || text.contains("throttl") {
+        Level::Warn
+    } else {
+        Level::Info
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Messaging) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_messaging_query(opts),
+        Box::new(messaging_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{messaging_row_to_entry, opsinsights_messaging_query};
+    use crate::{
+        examples::messaging_metric_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_dead_lettering_is_warn() {
+        let res = messaging_row_to_entry(messaging_metric_row());
+        assert_that(&res.group()).is_equal_to("aztail-sb");
+        assert_that(&res.unit()).is_equal_to("orders");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to("DeadletteredMessages (count 3)");
+    }
+
+    #[test]
+    pub fn row_to_entry_sets_level() {
+        let mut row = messaging_metric_row();
+        row.insert("Activity".to_owned(), json!("IncomingMessages"));
+        assert_that(&messaging_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("Activity".to_owned(), json!("ThrottledRequests"));
+        assert_that(&messaging_row_to_entry(row.clone()).level()).is_equal_to(Level::Warn);
+        row.insert("Status".to_owned(), json!("Failed"));
+        assert_that(&messaging_row_to_entry(row).level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--namespace-name", "aztail-sb"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_messaging_query(&opts).to_string();
+        assert_that!(query).starts_with("union isfuzzy=true (AZMSOperationalLogs");
+        assert_that!(query).contains("Namespace =~ 'aztail-sb'");
+    }
+}
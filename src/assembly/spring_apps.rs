@@ -0,0 +1,122 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+const APP_LOGS: &str = "AppPlatformLogsforSpring \
+    | project TimeGenerated, AppName, InstanceName, Level, \
+    Logger = tostring(column_ifexists('Logger', '')), Log";
+
+/// System logs come from the managed components (config server, service
+/// registry, gateway), which take the place of the app.
+const SYSTEM_LOGS: &str = "AppPlatformSystemLogs \
+    | project TimeGenerated, AppName = LogType, InstanceName, Level, \
+    Logger = tostring(column_ifexists('Logger', '')), Log";
+
+fn opsinsights_spring_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.spring_app.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.spring_app
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("AppName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.spring_instance.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.spring_instance
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("InstanceName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![APP_LOGS.to_owned(), SYSTEM_LOGS.to_owned()]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn spring_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("AppName")).to_owned();
+    let unit = as_str_or_empty(row.get("InstanceName")).to_owned();
+    let level = match as_str_or_empty(row.get("Level")).to_uppercase().as_str() {
+        "FATAL" | "ERROR" => Level::Error,
+        "WARN" | "WARNING" => Level::Warn,
+        "DEBUG" | "TRACE" => Level::Verbose,
+        _ => Level::Info,
+    };
+    let log = as_str_or_empty(row.get("Log")).trim_end();
+    let message = match as_str_or_empty(row.get("Logger")) {
+        "" => log.to_owned(),
+        logger => format!("[{}] {}", logger, log),
+    };
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::SpringApps) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_spring_query(opts),
+        Box::new(spring_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{opsinsights_spring_query, spring_row_to_entry};
+    use crate::{
+        examples::spring_app_row, options::cli_opts, source::Level, testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_prefixes_logger() {
+        let res = spring_row_to_entry(spring_app_row());
+        assert_that(&res.group()).is_equal_to("orders-api");
+        assert_that(&res.unit()).is_equal_to("orders-api-default-7-6f9c8d5b4-x2kqp");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to(
+            "[o.s.w.s.m.s.DefaultHandlerExceptionResolver] Resolved [org.springframework.web.HttpRequestMethodNotSupportedException: Request method 'GET' not supported]",
+        );
+    }
+
+    #[test]
+    pub fn row_to_entry_maps_level() {
+        let mut row = spring_app_row();
+        row.insert("Level".to_owned(), json!("ERROR"));
+        assert_that(&spring_row_to_entry(row.clone()).level()).is_equal_to(Level::Error);
+        row.insert("Level".to_owned(), json!("debug"));
+        assert_that(&spring_row_to_entry(row.clone()).level()).is_equal_to(Level::Verbose);
+        row.insert("Level".to_owned(), json!("INFO"));
+        assert_that(&spring_row_to_entry(row).level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--spring-app", "orders-api"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_spring_query(&opts).to_string();
+        assert_that!(query).starts_with("union isfuzzy=true (AppPlatformLogsforSpring");
+        assert_that!(query).contains("AppName =~ 'orders-api'");
+    }
+}
@@ -0,0 +1,204 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, EqIgnoreCase, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+const SQL_CATEGORIES: [&str; 4] = ["Errors", "Timeouts", "Blocks", "Deadlocks"];
+
+fn opsinsights_sql_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = vec![
+        Filter::boxed(Eq::new(
+            "ResourceProvider".to_owned(),
+            "MICROSOFT.SQL".to_owned(),
+        )),
+        Filter::boxed(Or::new(
+            SQL_CATEGORIES
+                .iter()
+                .map(|c| Eq::boxed("Category".to_owned(), (*c).to_owned()))
+                .collect(),
+        )),
+    ];
+    if !opts.sql_server.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.sql_server
+                .iter()
+                .cloned()
+                .map(|n| EqIgnoreCase::boxed("LogicalServerName_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.database.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.database
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("DatabaseName_s".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    Query::new("AzureDiagnostics".to_owned(), timespan, operators)
+}
+
+fn sql_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("LogicalServerName_s")).to_lowercase();
+    let unit = as_str_or_empty(row.get("DatabaseName_s")).to_owned();
+    let number = |column| row.get(column).and_then(Value::as_f64).map(|f| f as i64);
+    let (level, message) = match as_str_or_empty(row.get("Category")) {
+        "Errors" => (
+            Level::Error,
+            format!(
+                "error {} severity {}: {}",
+                number("error_number_d").unwrap_or_default(),
+                number("Severity").unwrap_or_default(),
+                as_str_or_empty(row.get("Message")).trim_end()
+            ),
+        ),
+        "Timeouts" => (
+            Level::Warn,
+            format!(
+                "timeout (state {}) query hash {}",
+                number("error_state_d").unwrap_or_default(),
+                as_str_or_empty(row.get("query_hash_s"))
+            ),
+        ),
+        "Blocks" => (
+            Level::Warn,
+            format!(
+                "blocked {} ms waiting for {} lock on {}",
+                number("duration_d").unwrap_or_default() / 1000,
+                as_str_or_empty(row.get("lock_mode_s")),
+                as_str_or_empty(row.get("resource_owner_type_s"))
+            ),
+        ),
+        "Deadlocks" => (
+            Level::Error,
+            deadlock_summary(as_str_or_empty(row.get("deadlock_xml_s"))),
+        ),
+        category => (Level::Info, category.to_owned()),
+    };
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+/// Summarises a deadlock graph on a single line, listing each process with
+/// the lock it waited for and its input buffer, e.g.
+/// `deadlock: process1 (victim) waits U on KEY: 5:720 [UPDATE ...]; process2 ...`.
+fn deadlock_summary(xml: &str) -> String {
+    let victim = xml
+        .split("<victimProcess ")
+        .nth(1)
+        .and_then(|tag| xml_attribute(tag, "id"));
+    let processes = xml
+        .split("<process ")
+        .skip(1)
+        .map(|process| {
+            let id = xml_attribute(process, "id").unwrap_or("?");
+            let input = process
+                .split("<inputbuf>")
+                .nth(1)
+                .and_then(|s| s.split("</inputbuf>").next())
+                .map(|s| unescape_xml(&s.split_whitespace().collect::<Vec<_>>().join(" ")))
+                .unwrap_or_default();
+            format!(
+                "{}{} waits {} on {} [{}]",
+                id,
+                if victim == Some(id) { " (victim)" } else { "" },
+                xml_attribute(process, "lockMode").unwrap_or("?"),
+                xml_attribute(process, "waitresource").unwrap_or("?"),
+                input
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("deadlock: {}", processes.join("; "))
+}
+
+/// Returns the value of an attribute on the first tag in `xml`.
+fn xml_attribute<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let tag = &xml[..xml.find('>').unwrap_or(xml.len())];
+    tag.split(&format!(" {}=\"", name))
+        .nth(1)
+        .or_else(|| tag.strip_prefix(&format!("{}=\"", name)))
+        .and_then(|s| s.split('"').next())
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::SqlDatabase) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_sql_query(opts),
+        Box::new(sql_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{opsinsights_sql_query, sql_row_to_entry};
+    use crate::{
+        examples::sql_deadlock_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_summarises_deadlock() {
+        let res = sql_row_to_entry(sql_deadlock_row());
+        assert_that(&res.group()).is_equal_to("aztail-sql");
+        assert_that(&res.unit()).is_equal_to("orders");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.message()).is_equal_to(
+            "deadlock: process1 (victim) waits U on KEY: 5:72057594043564032 (8194443284a0) [(@id int)UPDATE dbo.Orders SET Status = 'Shipped' WHERE Id = @id]; \
+            process2 waits X on KEY: 5:72057594043629568 (61a06abd401c) [(@id int)UPDATE dbo.OrderLines SET Quantity = Quantity - 1 WHERE OrderId > @id]",
+        );
+    }
+
+    #[test]
+    pub fn row_to_entry_timeout_is_warn() {
+        let mut row = sql_deadlock_row();
+        row.insert("Category".to_owned(), json!("Timeouts"));
+        row.insert("error_state_d".to_owned(), json!(2));
+        row.insert("query_hash_s".to_owned(), json!("0x8C2A5A5F0E3B1D77"));
+        let res = sql_row_to_entry(row);
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to("timeout (state 2) query hash 0x8C2A5A5F0E3B1D77");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec![
+            "--sql-server",
+            "aztail-sql",
+            "--database",
+            "orders",
+        ]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_sql_query(&opts).to_string();
+        assert_that!(query).contains("ResourceProvider == 'MICROSOFT.SQL'");
+        assert_that!(query).contains("LogicalServerName_s =~ 'aztail-sql'");
+        assert_that!(query).contains("DatabaseName_s == 'orders'");
+    }
+}
@@ -0,0 +1,146 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339};
+
+const STORAGE_TABLES: [&str; 3] = ["StorageBlobLogs", "StorageQueueLogs", "StorageTableLogs"];
+
+fn opsinsights_storage_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.storage_account.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.storage_account
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("AccountName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    // Accept both numeric codes (403) and status texts (AuthorizationFailure)
+    if !opts.status.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.status
+                .iter()
+                .cloned()
+                .flat_map(|n| {
+                    [
+                        Eq::boxed("StatusCode".to_owned(), n.clone()),
+                        Eq::boxed("StatusText".to_owned(), n),
+                    ]
+                })
+                .collect(),
+        )));
+    }
+    if !opts.operation.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.operation
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("OperationName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(STORAGE_TABLES.iter().map(|t| (*t).to_owned()).collect());
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn storage_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = as_str_or_empty(row.get("AccountName")).to_owned();
+    let unit = as_str_or_empty(row.get("ServiceType")).to_owned();
+    let level = match as_str_or_empty(row.get("StatusCode")).parse::<i64>() {
+        Ok(status) if status >= 500 => Level::Error,
+        Ok(status) if status >= 400 => Level::Warn,
+        _ => Level::Info,
+    };
+    let message = storage_message_line(&row);
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+fn storage_message_line(row: &Map<String, Value>) -> String {
+    let client_ip = as_str_or_empty(row.get("CallerIpAddress"));
+    let operation = as_str_or_empty(row.get("OperationName"));
+    let uri = as_str_or_empty(row.get("Uri"));
+    let status_code = as_str_or_empty(row.get("StatusCode"));
+    let status_text = as_str_or_empty(row.get("StatusText"));
+    let response_size = row.get("ResponseBodySize").unwrap_or(&Value::Null);
+    let duration = row.get("DurationMs").unwrap_or(&Value::Null);
+    let authentication = as_str_or_empty(row.get("AuthenticationType"));
+    format!(
+        "{} {} \"{}\" {} {} {} {} {}",
+        client_ip,
+        operation,
+        uri,
+        status_code,
+        status_text,
+        response_size,
+        duration,
+        authentication
+    )
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Storage) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_storage_query(opts),
+        Box::new(storage_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{opsinsights_storage_query, storage_row_to_entry};
+    use crate::{
+        examples::storage_blob_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_is_access_log_line() {
+        let res = storage_row_to_entry(storage_blob_row());
+        assert_that(&res.group()).is_equal_to("aztailstorage");
+        assert_that(&res.unit()).is_equal_to("blob");
+        assert_that(&res.level()).is_equal_to(Level::Warn);
+        assert_that(&res.message()).is_equal_to(
+            "20.50.2.17:50312 GetBlob \"https://aztailstorage.blob.core.windows.net/azure-webjobs-hosts/locks/aztail-func/host\" 403 AuthorizationPermissionMismatch 279 4 OAuth",
+        );
+    }
+
+    #[test]
+    pub fn row_to_entry_sets_level() {
+        let mut row = storage_blob_row();
+        row.insert("StatusCode".to_owned(), json!("200"));
+        assert_that(&storage_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("StatusCode".to_owned(), json!("503"));
+        assert_that(&storage_row_to_entry(row).level()).is_equal_to(Level::Error);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--status", "403", "--operation", "GetBlob"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_storage_query(&opts).to_string();
+        assert_that!(query).starts_with("union isfuzzy=true (StorageBlobLogs)");
+        assert_that!(query).contains("StatusCode == '403' or StatusText == '403'");
+        assert_that!(query).contains("OperationName == 'GetBlob'");
+    }
+}
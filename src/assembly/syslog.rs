@@ -0,0 +1,115 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+fn opsinsights_syslog_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("EventTime".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.computer.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.computer
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Computer".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.process.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.process
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("ProcessName".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.facility.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.facility
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Facility".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("EventTime".to_owned()));
+    Query::new("Syslog".to_owned(), timespan, operators)
+}
+
+fn syslog_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("EventTime"));
+    let group = unwrap_as_str(row.get("Computer")).to_owned();
+    let unit = as_str_or_empty(row.get("ProcessName")).to_owned();
+    let level = match as_str_or_empty(row.get("SeverityLevel")) {
+        "emerg" | "alert" | "crit" | "err" => Level::Error,
+        "warning" => Level::Warn,
+        "debug" => Level::Verbose,
+        _ => Level::Info,
+    };
+    let message = match row.get("ProcessID").and_then(Value::as_i64) {
+        Some(pid) => format!("[{}] {}", pid, unwrap_as_str(row.get("SyslogMessage"))),
+        None => unwrap_as_str(row.get("SyslogMessage")).to_owned(),
+    };
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::Syslog) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_syslog_query(opts),
+        Box::new(syslog_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{opsinsights_syslog_query, syslog_row_to_entry};
+    use crate::{
+        examples::syslog_row, options::cli_opts, source::Level, testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_maps_severity_to_level() {
+        let mut row = syslog_row();
+        assert_that(&syslog_row_to_entry(row.clone()).level()).is_equal_to(Level::Info);
+        row.insert("SeverityLevel".to_owned(), json!("crit"));
+        assert_that(&syslog_row_to_entry(row.clone()).level()).is_equal_to(Level::Error);
+        row.insert("SeverityLevel".to_owned(), json!("warning"));
+        assert_that(&syslog_row_to_entry(row.clone()).level()).is_equal_to(Level::Warn);
+        row.insert("SeverityLevel".to_owned(), json!("debug"));
+        assert_that(&syslog_row_to_entry(row).level()).is_equal_to(Level::Verbose);
+    }
+
+    #[test]
+    pub fn row_to_entry_includes_pid() {
+        let res = syslog_row_to_entry(syslog_row());
+        assert_that(&res.message()).starts_with("[1096] ");
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args = opsinsights_base_args().chain(vec!["--computer", "ze-vm", "--facility", "cron"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_syslog_query(&opts);
+        assert_that!(query.to_string()).contains("Computer == 'ze-vm'");
+        assert_that!(query.to_string()).contains("Facility == 'cron'");
+    }
+}
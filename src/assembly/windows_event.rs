@@ -0,0 +1,133 @@
+use serde_json::{Map, Value};
+
+use crate::options::{Opts, Service};
+use crate::{
+    kusto::{Eq, Filter, Operator, Or, Ordering, Query, Timespan, Union},
+    source::{opsinsight::OpsLogs, Level, LogEntry, LogSource},
+};
+
+use super::{as_str_or_empty, unwrap_as_rfc3339, unwrap_as_str};
+
+/// Events collected by the legacy Log Analytics agent.
+const EVENT: &str = "Event \
+    | project TimeGenerated, Computer, EventLog, Source, EventID = tostring(EventID), \
+    EventLevelName, Message = RenderedDescription";
+
+/// Events collected by the Azure Monitor agent carry no rendered description,
+/// only the event data.
+const WINDOWS_EVENT: &str = "WindowsEvent \
+    | project TimeGenerated, Computer, EventLog = Channel, Source = Provider, \
+    EventID = tostring(EventID), EventLevelName, Message = tostring(Data)";
+
+fn opsinsights_windows_event_query(opts: &Opts) -> Query {
+    let timespan = Timespan::new("TimeGenerated".to_owned(), opts.start_time, opts.end_time);
+    let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+    if !opts.computer.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.computer
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("Computer".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.event_log.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.event_log
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("EventLog".to_owned(), n))
+                .collect(),
+        )));
+    }
+    if !opts.event_id.is_empty() {
+        operators.push(Filter::boxed(Or::new(
+            opts.event_id
+                .iter()
+                .cloned()
+                .map(|n| Eq::boxed("EventID".to_owned(), n))
+                .collect(),
+        )));
+    }
+    operators.push(Ordering::boxed("TimeGenerated".to_owned()));
+    let tables = Union::new(vec![EVENT.to_owned(), WINDOWS_EVENT.to_owned()]);
+    Query::new(tables.to_string(), timespan, operators)
+}
+
+fn windows_event_row_to_entry(row: Map<String, Value>) -> LogEntry {
+    let timestamp = unwrap_as_rfc3339(row.get("TimeGenerated"));
+    let group = unwrap_as_str(row.get("Computer")).to_owned();
+    let unit = as_str_or_empty(row.get("Source")).to_owned();
+    let level = match as_str_or_empty(row.get("EventLevelName")) {
+        "Critical" | "Error" => Level::Error,
+        "Warning" => Level::Warn,
+        "Verbose" => Level::Verbose,
+        _ => Level::Info,
+    };
+    let message = format!(
+        "[{}] {}",
+        as_str_or_empty(row.get("EventID")),
+        as_str_or_empty(row.get("Message")).trim_end()
+    );
+    LogEntry {
+        timestamp,
+        group,
+        unit,
+        level,
+        message,
+        raw: row,
+    }
+}
+
+pub fn opsinsights(opts: &Opts) -> impl IntoIterator<Item = Box<dyn LogSource>> {
+    if opts.workspace.is_none() || !opts.requested_services().contains(&Service::WindowsEvents) {
+        return None;
+    }
+    Some(OpsLogs::boxed(
+        opsinsights_windows_event_query(opts),
+        Box::new(windows_event_row_to_entry),
+        opts.clone(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{opsinsights_windows_event_query, windows_event_row_to_entry};
+    use crate::{
+        examples::windows_event_row, options::cli_opts, source::Level,
+        testing::opsinsights_base_args,
+    };
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    pub fn row_to_entry_includes_event_id() {
+        let res = windows_event_row_to_entry(windows_event_row());
+        assert_that(&res.group()).is_equal_to("aztail-win");
+        assert_that(&res.unit()).is_equal_to("Service Control Manager");
+        assert_that(&res.level()).is_equal_to(Level::Error);
+        assert_that(&res.message()).is_equal_to(
+            "[7031] The Azure Pipelines Agent service terminated unexpectedly.  It has done this 1 time(s).",
+        );
+    }
+
+    #[test]
+    pub fn row_to_entry_maps_level_name_to_level() {
+        let mut row = windows_event_row();
+        row.insert("EventLevelName".to_owned(), json!("Warning"));
+        assert_that(&windows_event_row_to_entry(row.clone()).level()).is_equal_to(Level::Warn);
+        row.insert("EventLevelName".to_owned(), json!("Information"));
+        assert_that(&windows_event_row_to_entry(row).level()).is_equal_to(Level::Info);
+    }
+
+    #[test]
+    pub fn generates_query() {
+        let args =
+            opsinsights_base_args().chain(vec!["--event-log", "System", "--event-id", "7031"]);
+        let opts = cli_opts(args).unwrap();
+        let query = opsinsights_windows_event_query(&opts).to_string();
+        assert_that!(query).starts_with("union isfuzzy=true (Event");
+        assert_that!(query).contains("EventLog == 'System'");
+        assert_that!(query).contains("EventID == '7031'");
+    }
+}
@@ -97,3 +97,722 @@ pub fn container_apps_row() -> Map<String, Value> {
     .unwrap()
     .clone()
 }
+
+pub fn container_apps_renamed_row() -> Map<String, Value> {
+    json!({
+        "Computer": "",
+        "ContainerAppName": "aztail-containerapp",
+        "ContainerGroupId_g": "26d54061-b088-4ad7-a8ca-bae0990785e4",
+        "ContainerGroupName": "aztail-containerapp--50rufil-6f767b7c8d-wjnh9",
+        "ContainerId_s": "c7d965c2003bfaac29d132a85ae7d7b23ca2794e2173e4c10e9522c4414a5d12",
+        "ContainerImage_s": "ubuntu:latest",
+        "ContainerName": "log-container",
+        "EnvironmentName_s": "proudbay-f42875ce",
+        "Log": "Stdout",
+        "MG": "",
+        "ManagementGroupName": "",
+        "RawData": "",
+        "RevisionName": "aztail-containerapp--50rufil",
+        "SourceSystem": "RestAPI",
+        "Stream": "stdout",
+        "TenantId": "a9d042e7-d348-4f1e-a5b2-fb817144f9d6",
+        "TimeGenerated": "2022-09-25T20:35:43.597Z",
+        "Type": "ContainerAppConsoleLogs_CL",
+        "_ResourceId": "",
+        "_timestamp_d": 1664138142.74275,
+        "logtag_s": "F",
+        "time_s": "",
+        "time_t": "2022-09-25T20:35:42.742Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn logic_apps_row() -> Map<String, Value> {
+    json!({
+        "Category": "WorkflowRuntime",
+        "OperationName": "Microsoft.Logic/workflows/workflowActionCompleted",
+        "Resource": "AZTAIL-LOGICAPP",
+        "ResourceGroup": "AZTAIL",
+        "ResourceProvider": "MICROSOFT.LOGIC",
+        "ResourceType": "WORKFLOWS",
+        "TimeGenerated": "2022-10-02T19:41:03.2231374Z",
+        "Type": "AzureDiagnostics",
+        "code_s": "OK",
+        "correlation_clientTrackingId_s": "08585368573311914413478862671CU00",
+        "endTime_t": "2022-10-02T19:41:03.1424862Z",
+        "error_code_s": "",
+        "error_message_s": "",
+        "resource_actionName_s": "ifconfig",
+        "resource_location_s": "westeurope",
+        "resource_originRunId_s": "08585368573311914413478862671CU00",
+        "resource_resourceGroupName_s": "aztail",
+        "resource_runId_s": "08585368573311914413478862671CU00",
+        "resource_triggerName_s": "",
+        "resource_workflowId_s": "3c1d5e1e7c4d4c0f8a3a2b1e4b6f3a9d",
+        "resource_workflowName_s": "aztail-logicapp",
+        "startTime_t": "2022-10-02T19:41:02.8924764Z",
+        "status_s": "Succeeded",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn syslog_row() -> Map<String, Value> {
+    json!({
+        "Computer": "aztail-vm",
+        "EventTime": "2022-10-09T11:02:17Z",
+        "Facility": "daemon",
+        "HostIP": "10.0.2.4",
+        "HostName": "aztail-vm",
+        "MG": "00000000-0000-0000-0000-000000000002",
+        "ProcessID": 1096,
+        "ProcessName": "systemd-resolved",
+        "SeverityLevel": "info",
+        "SourceSystem": "Linux",
+        "SyslogMessage": "Server returned error NXDOMAIN, mitigating potential DNS violation DVE-2018-0001.",
+        "TenantId": "a9d042e7-d348-4f1e-a5b2-fb817144f9d6",
+        "TimeGenerated": "2022-10-09T11:02:18.372Z",
+        "Type": "Syslog",
+        "_ResourceId": "/subscriptions/00000000-0000-0000-0000-000000000000/resourcegroups/aztail/providers/microsoft.compute/virtualmachines/aztail-vm",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn container_instances_row() -> Map<String, Value> {
+    json!({
+        "Computer": "",
+        "ContainerGroup_s": "aztail-container",
+        "ContainerID_s": "caas-6e7f1e1b3b5b4b6c8d5e0f0a9b8c7d6e-1664138142",
+        "ContainerImage_s": "ubuntu:latest",
+        "ContainerName_s": "log-container",
+        "Location_s": "westeurope",
+        "Message": "Stderr",
+        "OSType_s": "Linux",
+        "RawData": "",
+        "ResourceGroup": "aztail",
+        "Source_s": "LoggingAgent",
+        "SourceSystem": "RestAPI",
+        "SubscriptionId_g": "00000000-0000-0000-0000-000000000000",
+        "TenantId": "a9d042e7-d348-4f1e-a5b2-fb817144f9d6",
+        "TimeGenerated": "2022-10-02T20:12:41.512Z",
+        "Type": "ContainerInstanceLog_CL",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn exceptions_functions_row() -> Map<String, Value> {
+    json!({
+        "timestamp": T1,
+        "cloud_RoleName": "ze-app",
+        "operation_Name": "ze-operation",
+        "problemId": "System.InvalidOperationException at Function.Run",
+        "type": "System.InvalidOperationException",
+        "outerType": "Microsoft.Azure.WebJobs.Host.FunctionInvocationException",
+        "outerMessage": "Exception while executing function: Functions.log-function",
+        "innermostType": "System.InvalidOperationException",
+        "innermostMessage": "ze-failure",
+        "severityLevel": 3,
+        "details": "[{\"outerId\":\"0\",\"message\":\"Exception while executing function: Functions.log-function\",\"type\":\"Microsoft.Azure.WebJobs.Host.FunctionInvocationException\",\"id\":\"46313592\"},{\"outerId\":\"46313592\",\"message\":\"ze-failure\",\"type\":\"System.InvalidOperationException\",\"id\":\"8131617\",\"parsedStack\":[{\"level\":0,\"method\":\"Function.Run\",\"assembly\":\"Function, Version=0.0.0.0\",\"fileName\":\"/home/site/wwwroot/run.csx\",\"line\":12},{\"level\":1,\"method\":\"Microsoft.Azure.WebJobs.Host.Executors.VoidTaskMethodInvoker.InvokeAsync\",\"assembly\":\"Microsoft.Azure.WebJobs.Host, Version=3.0.32.0\",\"line\":0}]}]",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn appexceptions_apim_row() -> Map<String, Value> {
+    json!({
+        "AppRoleName": "aztail-apim.azure-api.net West Europe",
+        "Details": "[{\"outerId\":\"0\",\"message\":\"Backend connection failure\",\"type\":\"BackendConnectionFailure\",\"id\":\"0\"}]",
+        "ExceptionType": "BackendConnectionFailure",
+        "InnermostMessage": "Backend connection failure",
+        "InnermostType": "BackendConnectionFailure",
+        "OperationName": "aztail-api;rev=1 - get-ping",
+        "OuterMessage": "Backend connection failure",
+        "OuterType": "BackendConnectionFailure",
+        "ProblemId": "BackendConnectionFailure",
+        "SeverityLevel": 3,
+        "TimeGenerated": "2021-12-22T22:56:48.164Z",
+        "Type": "AppExceptions",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn app_service_console_row() -> Map<String, Value> {
+    json!({
+        "ContainerId": "41c0e1ba9ef4f22a3ab4d1a1a8b3ec7a3f2f7fc5d2a0d1f68f9a0f29b1f3e0f1",
+        "Host": "lw1sdlwk0001GP",
+        "Level": "Informational",
+        "OperationName": "Microsoft.Web/sites/log",
+        "ResultDescription": "Listening on port 8080\n",
+        "Slot": "production",
+        "SourceSystem": "Azure",
+        "TimeGenerated": "2022-10-16T09:12:03.512Z",
+        "Type": "AppServiceConsoleLogs",
+        "WebApp": "aztail-webapp",
+        "_ResourceId": "/SUBSCRIPTIONS/00000000-0000-0000-0000-000000000000/RESOURCEGROUPS/AZTAIL/PROVIDERS/MICROSOFT.WEB/SITES/AZTAIL-WEBAPP",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn app_service_http_row() -> Map<String, Value> {
+    json!({
+        "CIp": "83.248.129.91",
+        "ComputerName": "RD0003FF5A8C5B",
+        "CsBytes": 512,
+        "CsCookie": "",
+        "CsHost": "aztail-webapp-staging.azurewebsites.net",
+        "CsMethod": "GET",
+        "CsUriQuery": "foo=bar",
+        "CsUriStem": "/api/ping",
+        "CsUsername": "",
+        "Referer": "",
+        "Result": "Success",
+        "ScBytes": 1245,
+        "ScStatus": 404,
+        "ScSubStatus": "0",
+        "ScWin32Status": "0",
+        "Slot": "staging",
+        "SPort": "443",
+        "TimeGenerated": "2022-10-16T09:12:04.028Z",
+        "TimeTaken": 31,
+        "Type": "AppServiceHTTPLogs",
+        "UserAgent": "curl/7.81.0",
+        "WebApp": "aztail-webapp",
+        "_ResourceId": "/SUBSCRIPTIONS/00000000-0000-0000-0000-000000000000/RESOURCEGROUPS/AZTAIL/PROVIDERS/MICROSOFT.WEB/SITES/AZTAIL-WEBAPP/SLOTS/STAGING",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn container_log_v2_row() -> Map<String, Value> {
+    json!({
+        "Computer": "aks-nodepool1-12345678-vmss000000",
+        "ContainerName": "api",
+        "LogMessage": "GET /healthz 200\n",
+        "LogSource": "stdout",
+        "PodName": "aztail-api-7d9c8b6f4-x2x9l",
+        "PodNamespace": "default",
+        "TimeGenerated": "2022-10-23T14:03:11.870Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn azure_activity_row() -> Map<String, Value> {
+    json!({
+        "ActivityStatusValue": "Success",
+        "ActivitySubstatusValue": "OK",
+        "Caller": "jane@example.com",
+        "CallerIpAddress": "83.248.129.91",
+        "CategoryValue": "Administrative",
+        "CorrelationId": "1d4c9e3a-5f6b-4a2e-9c1d-2b3a4c5d6e7f",
+        "Level": "Informational",
+        "OperationNameValue": "MICROSOFT.WEB/SITES/WRITE",
+        "ResourceGroup": "AZTAIL",
+        "ResourceProviderValue": "MICROSOFT.WEB",
+        "SubscriptionId": "00000000-0000-0000-0000-000000000000",
+        "TimeGenerated": "2022-10-30T08:41:26.117Z",
+        "Type": "AzureActivity",
+        "_ResourceId": "/subscriptions/00000000-0000-0000-0000-000000000000/resourcegroups/aztail/providers/microsoft.web/sites/aztail-function",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn dependencies_row() -> Map<String, Value> {
+    json!({
+        "timestamp": T1,
+        "cloud_RoleName": "ze-app",
+        "operation_Name": "ze-operation",
+        "type": "HTTP",
+        "target": "ifconfig.co",
+        "name": "GET /json",
+        "data": "https://ifconfig.co/json",
+        "resultCode": "200",
+        "success": "True",
+        "duration": 118.2236,
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn appdependencies_row() -> Map<String, Value> {
+    json!({
+        "AppRoleName": "aztail-function",
+        "Data": "SELECT 1",
+        "DependencyType": "SQL",
+        "DurationMs": 30012.5,
+        "Name": "aztail.database.windows.net | aztail",
+        "OperationName": "log-function",
+        "ResultCode": "-2",
+        "Success": false,
+        "Target": "aztail.database.windows.net | aztail",
+        "TimeGenerated": "2021-12-30T23:16:00Z",
+        "Type": "AppDependencies",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn container_apps_system_row() -> Map<String, Value> {
+    json!({
+        "ContainerAppName": "aztail-containerapp",
+        "EventType": "Warning",
+        "Log": "Container 'log-container' was terminated with exit code '1'",
+        "Reason": "ContainerCrashing",
+        "ReplicaName": "aztail-containerapp--50rufil-6f767b7c8d-wjnh9",
+        "RevisionName": "aztail-containerapp--50rufil",
+        "TimeGenerated": "2022-09-25T20:36:12.208Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn container_apps_resource_specific_row() -> Map<String, Value> {
+    json!({
+        "ContainerAppName": "aztail-containerapp",
+        "ContainerGroupId": "26d54061-b088-4ad7-a8ca-bae0990785e4",
+        "ContainerGroupName": "aztail-containerapp--50rufil-6f767b7c8d-wjnh9",
+        "ContainerId": "c7d965c2003bfaac29d132a85ae7d7b23ca2794e2173e4c10e9522c4414a5d12",
+        "ContainerImage": "ubuntu:latest",
+        "ContainerName": "log-container",
+        "EnvironmentName": "proudbay-f42875ce",
+        "Log": "Stderr",
+        "RevisionName": "aztail-containerapp--50rufil",
+        "Stream": "stderr",
+        "TimeGenerated": "2023-03-12T10:22:04.118Z",
+        "Type": "ContainerAppConsoleLogs",
+        "_ResourceId": "/subscriptions/00000000-0000-0000-0000-000000000000/resourcegroups/aztail/providers/microsoft.app/managedenvironments/proudbay",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn apim_gateway_row() -> Map<String, Value> {
+    json!({
+        "ApiId": "aztail-api",
+        "ApiRevision": "1",
+        "ApimService": "aztail-apim",
+        "BackendMethod": "GET",
+        "BackendResponseCode": 0,
+        "BackendTime": 0,
+        "BackendUrl": "https://aztail-func.azurewebsites.net/api/ping",
+        "CallerIpAddress": "83.248.129.91",
+        "IsRequestSuccess": false,
+        "LastErrorMessage": "The operation was canceled.",
+        "LastErrorReason": "BackendConnectionFailure",
+        "Method": "GET",
+        "OperationId": "get-ping",
+        "ResponseCode": 502,
+        "ResponseSize": 210,
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+        "TotalTime": 20024.4,
+        "Type": "ApiManagementGatewayLogs",
+        "Url": "https://aztail-apim.azure-api.net/ping",
+        "_ResourceId": "/subscriptions/00000000-0000-0000-0000-000000000000/resourcegroups/aztail/providers/microsoft.apimanagement/service/aztail-apim",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn app_gateway_access_row() -> Map<String, Value> {
+    json!({
+        "Category": "ApplicationGatewayAccessLog",
+        "OperationName": "ApplicationGatewayAccess",
+        "Resource": "AZTAIL-AGW",
+        "ResourceGroup": "AZTAIL",
+        "ResourceType": "APPLICATIONGATEWAYS",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+        "backendPoolName_s": "apim-pool",
+        "clientIP_s": "83.248.129.91",
+        "clientPort_d": 51324.0,
+        "host_s": "api.aztail.example",
+        "httpMethod_s": "GET",
+        "httpStatus_d": 403.0,
+        "listenerName_s": "https-listener",
+        "receivedBytes_d": 512.0,
+        "requestQuery_s": "foo=bar",
+        "requestUri_s": "/api/ping",
+        "ruleName_s": "api-rule",
+        "sentBytes_d": 345.0,
+        "serverRouted_s": "",
+        "serverStatus_s": "",
+        "timeTaken_d": 0.004,
+        "userAgent_s": "curl/7.79.1",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn front_door_firewall_row() -> Map<String, Value> {
+    json!({
+        "Category": "FrontDoorWebApplicationFirewallLog",
+        "OperationName": "Microsoft.Network/FrontDoor/WebApplicationFirewallLog/Write",
+        "Resource": "AZTAIL-FD",
+        "ResourceGroup": "AZTAIL",
+        "ResourceType": "FRONTDOORS",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+        "action_s": "Block",
+        "clientIP_s": "83.248.129.91",
+        "clientPort_s": "51324",
+        "host_s": "aztail-fd.azurefd.net",
+        "policyMode_s": "prevention",
+        "policy_s": "aztailwaf",
+        "requestUri_s": "https://aztail-fd.azurefd.net:443/api/ping?id=1%27%20OR%201=1",
+        "ruleName_s": "DefaultRuleSet-1.0-SQLI-942100",
+        "socketIP_s": "83.248.129.91",
+        "trackingReference_s": "0x8mYYgAAAAA",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn traces_application_row() -> Map<String, Value> {
+    json!({
+        "timestamp": T1,
+        "message": "Order 4711 not found",
+        "severityLevel": 2,
+        "itemType": "trace",
+        "customDimensions": "{\"CategoryName\":\"Orders.Controllers.OrderController\",\"OrderId\":\"4711\"}",
+        "operation_Name": "GET Order/Get [id]",
+        "operation_Id": "b6fd3bd5a27e6e41a3c5dbb4b7bbd6a8",
+        "cloud_RoleName": "orders-api",
+        "cloud_RoleInstance": "orders-api-7d9f8b6c4-x2lkq",
+        "sdkVersion": "dotnetc:2.21.0-429",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn apptraces_application_row() -> Map<String, Value> {
+    json!({
+        "TimeGenerated": T1,
+        "Message": "Invoice 17 sent",
+        "SeverityLevel": 1,
+        "Properties": "{\"LoggerName\":\"com.example.billing.InvoiceService\",\"ThreadName\":\"main\"}",
+        "OperationName": "POST /invoices",
+        "AppRoleName": "billing",
+        "AppRoleInstance": "billing-5c6d7f9b8-qwz4r",
+        "SDKVersion": "java:3.4.7",
+        "Type": "AppTraces",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn kube_events_row() -> Map<String, Value> {
+    json!({
+        "ClusterName": "aztail-aks",
+        "Computer": "aks-nodepool1-12345678-vmss000000",
+        "Count": 14.0,
+        "FirstSeen": "2022-10-23T13:51:02Z",
+        "KubeEventType": "Warning",
+        "LastSeen": "2022-10-23T14:03:11Z",
+        "Message": "Back-off restarting failed container api in pod aztail-api-7d9c8b6f4-x2x9l_default(3f1c2e9a-5b1d-4c7e-9a0b-2d6e8f4a1c3b)",
+        "Name": "aztail-api-7d9c8b6f4-x2x9l",
+        "Namespace": "default",
+        "ObjectKind": "Pod",
+        "Reason": "BackOff",
+        "SourceComponent": "kubelet",
+        "TimeGenerated": "2022-10-23T14:03:15.000Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn aks_audit_row() -> Map<String, Value> {
+    json!({
+        "Category": "kube-audit",
+        "Level": "RequestResponse",
+        "Namespace": "default",
+        "ObjectName": "aztail-api",
+        "ObjectRef": "{\"resource\":\"deployments\",\"namespace\":\"default\",\"name\":\"aztail-api\",\"apiGroup\":\"apps\",\"apiVersion\":\"v1\"}",
+        "ObjectResource": "deployments",
+        "RequestUri": "/apis/apps/v1/namespaces/default/deployments/aztail-api",
+        "ResponseStatus": "{\"metadata\":{},\"status\":\"Failure\",\"reason\":\"Forbidden\",\"code\":403}",
+        "SourceIps": "[\"83.248.129.91\"]",
+        "Stage": "ResponseComplete",
+        "StatusCode": 403,
+        "TimeGenerated": "2022-10-23T14:04:20.512Z",
+        "User": "{\"username\":\"alice@example.com\",\"groups\":[\"system:authenticated\"]}",
+        "Username": "alice@example.com",
+        "Verb": "patch",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn managed_identity_signin_row() -> Map<String, Value> {
+    json!({
+        "AppDisplayName": "aztail-func",
+        "IPAddress": "20.50.2.17",
+        "Principal": "aztail-func",
+        "ResourceDisplayName": "Azure Key Vault",
+        "ResultDescription": "Application with identifier was not found in the directory.",
+        "ResultType": "700016",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn audit_logs_row() -> Map<String, Value> {
+    json!({
+        "AppDisplayName": "",
+        "Category": "GroupManagement",
+        "InitiatedBy": "{\"user\":{\"userPrincipalName\":\"alice@example.com\",\"ipAddress\":\"83.248.129.91\"}}",
+        "LoggedByService": "Core Directory",
+        "OperationName": "Add member to group",
+        "Principal": "alice@example.com",
+        "Result": "success",
+        "ResultReason": "",
+        "Target": "aztail-operators",
+        "TargetResources": "[{\"displayName\":\"aztail-operators\",\"type\":\"Group\"}]",
+        "TimeGenerated": "2022-05-02T19:45:03.1203554Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn key_vault_row() -> Map<String, Value> {
+    json!({
+        "Category": "AuditEvent",
+        "CallerIPAddress": "20.50.2.17",
+        "DurationMs": 12.0,
+        "OperationName": "SecretGet",
+        "Resource": "AZTAIL-KV",
+        "ResourceProvider": "MICROSOFT.KEYVAULT",
+        "ResultSignature": "Forbidden",
+        "ResultType": "Success",
+        "SecretName": "db-password",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+        "httpStatusCode_d": 403.0,
+        "id_s": "https://aztail-kv.vault.azure.net/secrets/db-password",
+        "identity_claim_appid_g": "9c1d7e4b-2a3f-4e5d-8b6c-7a8f9e0d1c2b",
+        "identity_claim_upn_s": "",
+        "requestUri_s": "https://aztail-kv.vault.azure.net/secrets/db-password/?api-version=7.3",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn storage_blob_row() -> Map<String, Value> {
+    json!({
+        "AccountName": "aztailstorage",
+        "AuthenticationType": "OAuth",
+        "CallerIpAddress": "20.50.2.17:50312",
+        "Category": "StorageRead",
+        "DurationMs": 4,
+        "ObjectKey": "/aztailstorage/azure-webjobs-hosts/locks/aztail-func/host",
+        "OperationName": "GetBlob",
+        "Protocol": "HTTPS",
+        "RequesterObjectId": "2f8d6c1e-3b4a-4c5d-9e8f-1a2b3c4d5e6f",
+        "ResponseBodySize": 279,
+        "ServerLatencyMs": 3,
+        "ServiceType": "blob",
+        "StatusCode": "403",
+        "StatusText": "AuthorizationPermissionMismatch",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+        "Type": "StorageBlobLogs",
+        "Uri": "https://aztailstorage.blob.core.windows.net/azure-webjobs-hosts/locks/aztail-func/host",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn messaging_metric_row() -> Map<String, Value> {
+    json!({
+        "Activity": "DeadletteredMessages",
+        "Count": 3,
+        "Detail": "",
+        "Entity": "orders",
+        "Namespace": "aztail-sb",
+        "Status": "",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn adf_activity_run_row() -> Map<String, Value> {
+    json!({
+        "Activity": "CopyOrdersToLake",
+        "Error": "Failure happened on 'Sink' side. ErrorCode=UserErrorFailedFileOperation\n",
+        "Factory": "aztail-adf",
+        "PipelineName": "copy-orders",
+        "RunId": "8a3c2d1e-4f5b-4a6c-9d7e-0f1a2b3c4d5e",
+        "Status": "Failed",
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn cosmos_db_request_row() -> Map<String, Value> {
+    json!({
+        "AccountName": "aztail-cosmos",
+        "ClientIpAddress": "20.50.2.17",
+        "CollectionName": "items",
+        "DatabaseName": "orders",
+        "DurationMs": 5.2,
+        "OperationName": "Query",
+        "RequestCharge": 2.86,
+        "StatusCode": 429,
+        "TimeGenerated": "2022-05-02T19:41:12.0743924Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn traces_durable_row() -> Map<String, Value> {
+    json!({
+        "timestamp": T1,
+        "cloud_RoleName": "aztail-func",
+        "operation_Name": "SendInvoice",
+        "message": "6f3b2a1c9d8e4f7a: Function 'SendInvoice (Activity)' failed with an error. Reason: System.TimeoutException: The operation has timed out.. IsReplay: False. State: Failed. HubName: aztailhub. AppName: aztail-func. SlotName: Production. ExtensionVersion: 2.9.0. SequenceNumber: 12. TaskEventId: 3",
+        "severityLevel": 3,
+        "customDimensions": "{\"Category\":\"Host.Triggers.DurableTask\",\"LogLevel\":\"Error\",\"prop__functionName\":\"SendInvoice\",\"prop__functionType\":\"Activity\",\"prop__hubName\":\"aztailhub\",\"prop__instanceId\":\"6f3b2a1c9d8e4f7a\",\"prop__isReplay\":\"False\",\"prop__reason\":\"System.TimeoutException: The operation has timed out.\",\"prop__state\":\"Failed\"}",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn windows_event_row() -> Map<String, Value> {
+    json!({
+        "Computer": "aztail-win",
+        "EventID": "7031",
+        "EventLevelName": "Error",
+        "EventLog": "System",
+        "Message": "The Azure Pipelines Agent service terminated unexpectedly.  It has done this 1 time(s).\r\n",
+        "Source": "Service Control Manager",
+        "TimeGenerated": "2022-10-09T11:04:52.413Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn sql_deadlock_row() -> Map<String, Value> {
+    json!({
+        "Category": "Deadlocks",
+        "DatabaseName_s": "orders",
+        "LogicalServerName_s": "aztail-sql",
+        "OperationName": "DeadlockEvent",
+        "Resource": "ORDERS",
+        "ResourceProvider": "MICROSOFT.SQL",
+        "TimeGenerated": "2022-10-09T11:07:31.220Z",
+        "deadlock_xml_s": "<deadlock><victim-list><victimProcess id=\"process1\" /></victim-list><process-list><process id=\"process1\" taskpriority=\"0\" logused=\"284\" waitresource=\"KEY: 5:72057594043564032 (8194443284a0)\" waittime=\"3412\" ownerId=\"9132\" transactionname=\"user_transaction\" lockMode=\"U\" isolationlevel=\"read committed (2)\"><executionStack><frame procname=\"unknown\" line=\"1\" stmtstart=\"38\">unknown</frame></executionStack><inputbuf>\n(@id int)UPDATE dbo.Orders SET Status = 'Shipped' WHERE Id = @id   </inputbuf></process><process id=\"process2\" taskpriority=\"0\" logused=\"312\" waitresource=\"KEY: 5:72057594043629568 (61a06abd401c)\" waittime=\"3398\" ownerId=\"9141\" transactionname=\"user_transaction\" lockMode=\"X\" isolationlevel=\"read committed (2)\"><executionStack><frame procname=\"unknown\" line=\"1\" stmtstart=\"38\">unknown</frame></executionStack><inputbuf>\n(@id int)UPDATE dbo.OrderLines SET Quantity = Quantity - 1 WHERE OrderId &gt; @id   </inputbuf></process></process-list><resource-list /></deadlock>",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn spring_app_row() -> Map<String, Value> {
+    json!({
+        "AppName": "orders-api",
+        "InstanceName": "orders-api-default-7-6f9c8d5b4-x2kqp",
+        "Level": "WARN",
+        "Log": "Resolved [org.springframework.web.HttpRequestMethodNotSupportedException: Request method 'GET' not supported]\n",
+        "Logger": "o.s.w.s.m.s.DefaultHandlerExceptionResolver",
+        "TimeGenerated": "2022-10-09T11:09:44.118Z",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn custom_events_row() -> Map<String, Value> {
+    json!({
+        "timestamp": T1,
+        "cloud_RoleName": "ze-app",
+        "operation_Name": "POST Orders/Create",
+        "name": "OrderPlaced",
+        "customDimensions": "{\"OrderId\":\"4711\",\"Channel\":\"web\",\"_MS.ProcessedByMetricExtractors\":\"(Name:'Events', Ver:'1.1')\"}",
+        "itemCount": 1,
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn appevents_row() -> Map<String, Value> {
+    json!({
+        "AppRoleName": "ze-app",
+        "ItemCount": 1,
+        "Name": "OrderPlaced",
+        "OperationName": "POST Orders/Create",
+        "Properties": {
+            "Channel": "web",
+            "OrderId": "4711",
+        },
+        "TimeGenerated": "2022-10-09T11:12:03.551Z",
+        "Type": "AppEvents",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn availability_row() -> Map<String, Value> {
+    json!({
+        "timestamp": T1,
+        "name": "orders-api ping",
+        "location": "West Europe",
+        "success": "0",
+        "message": "Request timed out after 30 seconds. Timeout was reached.",
+        "duration": 30000,
+        "cloud_RoleName": "",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+pub fn appavailability_row() -> Map<String, Value> {
+    json!({
+        "DurationMs": 30000,
+        "Location": "West Europe",
+        "Message": "Request timed out after 30 seconds. Timeout was reached.",
+        "Name": "orders-api ping",
+        "Success": false,
+        "TimeGenerated": "2022-10-09T11:13:00Z",
+        "Type": "AppAvailabilityResults",
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
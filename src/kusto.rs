@@ -1,9 +1,17 @@
 use chrono::prelude::*;
+use chrono::Duration;
 use std::any::Any;
 use std::fmt::{self, Debug, Display, Formatter};
 
+enum Table {
+    Named(String),
+    /// Rendered from the query's timespan on every run, so that lookups
+    /// inside it stay within the range the query is advanced to.
+    Timed(fn(&Timespan) -> String),
+}
+
 pub struct Query {
-    table: String,
+    table: Table,
     timespan: Timespan,
     operators: Vec<Box<dyn Operator>>,
 }
@@ -11,7 +19,19 @@ pub struct Query {
 impl Query {
     pub fn new(table: String, timespan: Timespan, operators: Vec<Box<dyn Operator>>) -> Self {
         Query {
-            table,
+            table: Table::Named(table),
+            timespan,
+            operators,
+        }
+    }
+
+    pub fn timed(
+        table: fn(&Timespan) -> String,
+        timespan: Timespan,
+        operators: Vec<Box<dyn Operator>>,
+    ) -> Self {
+        Query {
+            table: Table::Timed(table),
             timespan,
             operators,
         }
@@ -27,9 +47,40 @@ impl Query {
     }
 }
 
+/// A tabular expression combining several tables, ignoring those that
+/// do not exist in the workspace.
+#[derive(Debug)]
+pub struct Union {
+    tables: Vec<String>,
+}
+
+impl Union {
+    pub fn new(tables: Vec<String>) -> Self {
+        Self { tables }
+    }
+}
+
+impl Display for Union {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "union isfuzzy=true ")?;
+        let mut first = true;
+        for table in &self.tables {
+            if !first {
+                write!(f, ", ")?;
+            }
+            first = false;
+            write!(f, "({})", table)?;
+        }
+        Ok(())
+    }
+}
+
 impl Display for Query {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.table, self.timespan)?;
+        match &self.table {
+            Table::Named(table) => write!(f, "{}{}", table, self.timespan)?,
+            Table::Timed(table) => write!(f, "{}{}", table(&self.timespan), self.timespan)?,
+        }
         for op in &self.operators {
             write!(f, "{}", op)?;
         }
@@ -93,6 +144,15 @@ impl Timespan {
     pub fn advance_start(&mut self, start_time: Option<DateTime<FixedOffset>>) {
         self.start_time = start_time;
     }
+
+    /// The same timespan, extended by `margin` at either end.
+    pub fn widened(&self, margin: Duration) -> Self {
+        Self {
+            field: self.field.clone(),
+            start_time: self.start_time.map(|t| t - margin),
+            end_time: self.end_time.map(|t| t + margin),
+        }
+    }
 }
 
 impl Operator for Timespan {}
@@ -134,6 +194,30 @@ impl Display for Ordering {
     }
 }
 
+#[derive(Debug)]
+pub struct Extend {
+    field: String,
+    expression: String,
+}
+
+impl Extend {
+    pub fn new(field: String, expression: String) -> Self {
+        Self { field, expression }
+    }
+
+    pub fn boxed(field: String, expression: String) -> Box<dyn Operator> {
+        Box::new(Self::new(field, expression)) as Box<dyn Operator>
+    }
+}
+
+impl Operator for Extend {}
+
+impl Display for Extend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, " | extend {} = {}", self.field, self.expression)
+    }
+}
+
 pub trait Expression: Any + Debug + Send + Sync + Display {}
 
 #[derive(Debug)]
@@ -160,6 +244,30 @@ impl Display for Eq {
     }
 }
 
+#[derive(Debug)]
+pub struct EqIgnoreCase {
+    field: String,
+    value: String,
+}
+
+impl EqIgnoreCase {
+    pub fn new(field: String, value: String) -> Self {
+        Self { field, value }
+    }
+
+    pub fn boxed(field: String, value: String) -> Box<dyn Expression> {
+        Box::new(Self::new(field, value)) as Box<dyn Expression>
+    }
+}
+
+impl Expression for EqIgnoreCase {}
+
+impl Display for EqIgnoreCase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} =~ '{}'", self.field, self.value)
+    }
+}
+
 #[derive(Debug)]
 pub struct StartsWith {
     field: String,
@@ -208,6 +316,49 @@ impl Display for EndsWith {
     }
 }
 
+#[derive(Debug)]
+pub struct Contains {
+    field: String,
+    value: String,
+}
+
+impl Contains {
+    pub fn new(field: String, value: String) -> Self {
+        Self { field, value }
+    }
+
+    pub fn boxed(field: String, value: String) -> Box<dyn Expression> {
+        Box::new(Self::new(field, value)) as Box<dyn Expression>
+    }
+}
+
+impl Expression for Contains {}
+
+impl Display for Contains {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} contains_cs '{}'", self.field, self.value)
+    }
+}
+
+#[derive(Debug)]
+pub struct Not {
+    expression: Box<dyn Expression>,
+}
+
+impl Not {
+    pub fn new(expression: Box<dyn Expression>) -> Self {
+        Self { expression }
+    }
+}
+
+impl Expression for Not {}
+
+impl Display for Not {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "not({})", self.expression)
+    }
+}
+
 #[derive(Debug)]
 pub struct Lt {
     field: String,
@@ -299,7 +450,10 @@ impl Display for Or {
 
 #[cfg(test)]
 mod test {
-    use super::{Eq, Filter, Ge, Gt, Lt, Or, Ordering, Query, Timespan};
+    use super::{
+        Contains, Eq, EqIgnoreCase, Extend, Filter, Ge, Gt, Lt, Not, Or, Ordering, Query, Timespan,
+        Union,
+    };
     use crate::examples::{T1, T2};
     use speculoos::prelude::*;
 
@@ -324,6 +478,26 @@ mod test {
         assert_that(&format!("{}", query)).contains("| where timestamp < datetime(");
     }
 
+    #[test]
+    fn timed_table_follows_timespan() {
+        let mut query = Query::timed(
+            |timespan| format!("traces | join (requests{}) on operation_Id", timespan),
+            Timespan::new("timestamp".to_owned(), T1.parse().ok(), None),
+            vec![],
+        );
+        query.advance_start(T2.parse().ok());
+        assert_that(&format!("{}", query)).is_equal_to(
+            "traces | join (requests | where timestamp > datetime(2021-11-20T06:18:31+00:00)) on operation_Id | where timestamp > datetime(2021-11-20T06:18:31+00:00)".to_owned(),
+        );
+    }
+
+    #[test]
+    fn union() {
+        let subject = Union::new(vec!["foo".to_owned(), "bar | extend baz = 1".to_owned()]);
+        assert_that(&format!("{}", subject))
+            .is_equal_to("union isfuzzy=true (foo), (bar | extend baz = 1)".to_owned());
+    }
+
     #[test]
     fn filter() {
         let subject = Filter::new(Eq::new("foo".to_owned(), "bar".to_owned()));
@@ -336,6 +510,31 @@ mod test {
         assert_that(&format!("{}", subject)).is_equal_to("ze-field == 'foo'".to_owned())
     }
 
+    #[test]
+    fn extend() {
+        let subject = Extend::new("foo".to_owned(), "tolower(bar)".to_owned());
+        assert_that(&format!("{}", subject)).is_equal_to(" | extend foo = tolower(bar)".to_owned());
+    }
+
+    #[test]
+    fn eq_ignore_case() {
+        let subject = EqIgnoreCase::new("ze-field".to_owned(), "Foo".to_owned());
+        assert_that(&format!("{}", subject)).is_equal_to("ze-field =~ 'Foo'".to_owned())
+    }
+
+    #[test]
+    fn contains() {
+        let subject = Contains::new("ze-field".to_owned(), "foo".to_owned());
+        assert_that(&format!("{}", subject)).is_equal_to("ze-field contains_cs 'foo'".to_owned())
+    }
+
+    #[test]
+    fn not() {
+        let subject = Not::new(Contains::boxed("ze-field".to_owned(), "foo".to_owned()));
+        assert_that(&format!("{}", subject))
+            .is_equal_to("not(ze-field contains_cs 'foo')".to_owned())
+    }
+
     #[test]
     fn lt() {
         let subject = Lt::new("ze-field".to_owned(), T1.parse().unwrap());
@@ -28,6 +28,8 @@ pub enum AzTailError {
     LogAnalyticsService,
     #[error("Invalid output format: {0}")]
     InvalidOutputFormat(String),
+    #[error("Invalid container service: {0}")]
+    InvalidContainerService(String),
 }
 
 fn build_presenter(opts: &options::Opts) -> Box<dyn Presenter> {
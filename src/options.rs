@@ -9,8 +9,48 @@ use std::str::FromStr;
 #[derive(Debug, PartialEq)]
 pub enum Service {
     APIManagement,
+    ActivityLog,
+    AppGateway,
+    Application,
+    Availability,
     ContainerApps,
+    ContainerInstances,
+    CosmosDb,
+    CustomEvents,
+    DataFactory,
+    Dependencies,
     Functions,
+    Identity,
+    KeyVault,
+    Kubernetes,
+    LogicApps,
+    Messaging,
+    SpringApps,
+    SqlDatabase,
+    Storage,
+    Syslog,
+    WebApps,
+    WindowsEvents,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContainerService {
+    Apps,
+    Instances,
+}
+
+impl FromStr for ContainerService {
+    type Err = super::AzTailError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        if lower == "apps" {
+            Ok(ContainerService::Apps)
+        } else if lower == "instances" {
+            Ok(ContainerService::Instances)
+        } else {
+            Err(AzTailError::InvalidContainerService(lower))
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -64,6 +104,9 @@ pub struct Opts {
     /// Debug log all queries and all entries received
     #[clap(long)]
     pub debug: bool,
+    /// Show only the first line of exceptions, without stack trace
+    #[clap(long)]
+    pub collapse_stack_traces: bool,
 
     // Azure Functions
     /// Show only logs for a specific app
@@ -72,22 +115,191 @@ pub struct Opts {
     /// Show only logs for a specific function
     #[clap(long)]
     pub function: Vec<String>,
+    /// Show only Durable Functions logs for an orchestration instance
+    #[clap(long)]
+    pub orchestration_id: Vec<String>,
+
+    // Applications on Application Insights
+    /// Show only logs for an application role (cloud_RoleName)
+    #[clap(long)]
+    pub role: Vec<String>,
+    /// Show only logs for a specific role instance (e.g. pod or host name)
+    #[clap(long)]
+    pub role_instance: Vec<String>,
+
+    // Application Insights dependencies
+    /// Show only outbound calls of a dependency type (e.g. SQL, HTTP)
+    #[clap(long)]
+    pub dependency_type: Vec<String>,
 
-    // Azure Container Instances
+    // Application Insights custom events and availability tests
+    /// Show only custom events with this name
+    #[clap(long)]
+    pub event_name: Vec<String>,
+    /// Show these customDimensions with custom events (e.g. OrderId)
+    #[clap(long)]
+    pub event_dimension: Vec<String>,
+    /// Show only results of a specific availability test
+    #[clap(long)]
+    pub test_name: Vec<String>,
+
+    // Azure Container Apps and Container Instances
     /// Show only logs for a container group
     #[clap(long)]
     pub container_group: Vec<String>,
     /// Show only logs for a specific container
     #[clap(long)]
     pub container_name: Vec<String>,
+    /// Restrict container logs to one of apps, instances
+    #[clap(long)]
+    pub container_service: Option<ContainerService>,
+    /// Show only Container Apps system logs (provisioning, restarts, probes)
+    #[clap(long, conflicts_with = "console-only")]
+    pub system_only: bool,
+    /// Show only Container Apps console logs
+    #[clap(long)]
+    pub console_only: bool,
 
     // Azure API management
-    /// Show only logs for a particular API
+    /// Show only logs for a particular API Management service (e.g. aztail-apim)
     #[clap(long)]
     pub api_name: Vec<String>,
     /// Show only logs for a particular operation (regardless of owning API)
     #[clap(long)]
     pub api_operation: Vec<String>,
+
+    // Azure Logic Apps
+    /// Show only logs for a specific workflow
+    #[clap(long)]
+    pub workflow: Vec<String>,
+    /// Show only logs for a specific workflow run
+    #[clap(long)]
+    pub workflow_run: Vec<String>,
+
+    // Azure VMs logging to Syslog or Windows Event Log
+    /// Show only logs for a specific computer
+    #[clap(long)]
+    pub computer: Vec<String>,
+    /// Show only logs for a specific process
+    #[clap(long)]
+    pub process: Vec<String>,
+    /// Show only logs for a syslog facility (e.g. daemon, cron)
+    #[clap(long)]
+    pub facility: Vec<String>,
+    /// Show only events from a Windows event log (e.g. System, Application)
+    #[clap(long)]
+    pub event_log: Vec<String>,
+    /// Show only Windows events with this event ID
+    #[clap(long)]
+    pub event_id: Vec<String>,
+
+    // Azure App Service
+    /// Show only logs for a specific web app
+    #[clap(long)]
+    pub web_app: Vec<String>,
+    /// Show only logs for a deployment slot (e.g. production, staging)
+    #[clap(long)]
+    pub slot: Vec<String>,
+
+    // Azure Kubernetes Service
+    /// Show only logs for a Kubernetes namespace
+    #[clap(long)]
+    pub namespace: Vec<String>,
+    /// Show only logs for pods whose name starts with this
+    #[clap(long)]
+    pub pod: Vec<String>,
+    /// Show only logs for a specific Kubernetes container
+    #[clap(long)]
+    pub container: Vec<String>,
+    /// Show only Kubernetes audit events with this verb (e.g. create, delete)
+    #[clap(long)]
+    pub verb: Vec<String>,
+
+    // Azure Activity Log
+    /// Show only control-plane events for a resource group
+    #[clap(long)]
+    pub resource_group: Vec<String>,
+    /// Show only control-plane events initiated by a user or principal
+    #[clap(long)]
+    pub caller: Vec<String>,
+
+    // Entra ID sign-in and audit logs
+    /// Show only sign-ins and directory changes by a user or service principal
+    #[clap(long)]
+    pub user_principal: Vec<String>,
+    /// Show only sign-ins to or directory changes by an application
+    #[clap(long)]
+    pub app_display_name: Vec<String>,
+
+    // Azure Key Vault
+    /// Show only audit events for a specific key vault
+    #[clap(long)]
+    pub vault: Vec<String>,
+    /// Show only audit events for a secret, key or certificate
+    #[clap(long)]
+    pub secret_name: Vec<String>,
+
+    // Azure Storage
+    /// Show only requests to a specific storage account
+    #[clap(long)]
+    pub storage_account: Vec<String>,
+    /// Show only storage requests with this status code or text (e.g. 403)
+    #[clap(long)]
+    pub status: Vec<String>,
+    /// Show only storage requests for an operation (e.g. GetBlob, PutMessage)
+    #[clap(long)]
+    pub operation: Vec<String>,
+
+    // Azure Service Bus and Event Hubs
+    /// Show only logs for a Service Bus or Event Hubs namespace
+    #[clap(long)]
+    pub namespace_name: Vec<String>,
+    /// Show only logs for a queue, topic or event hub
+    #[clap(long)]
+    pub entity: Vec<String>,
+
+    // Azure Data Factory
+    /// Show only runs in a specific data factory
+    #[clap(long)]
+    pub factory: Vec<String>,
+    /// Show only runs of a specific pipeline
+    #[clap(long)]
+    pub pipeline: Vec<String>,
+    /// Show only a specific pipeline run
+    #[clap(long)]
+    pub run_id: Vec<String>,
+
+    // Azure Cosmos DB
+    /// Show only requests to a specific Cosmos DB account
+    #[clap(long)]
+    pub cosmos_account: Vec<String>,
+    /// Show only requests to a specific database
+    #[clap(long)]
+    pub database: Vec<String>,
+    /// Show only requests to a specific Cosmos DB collection or container
+    #[clap(long)]
+    pub collection: Vec<String>,
+
+    // Azure Spring Apps
+    /// Show only logs for a Spring app or managed component (e.g. ConfigServer)
+    #[clap(long)]
+    pub spring_app: Vec<String>,
+    /// Show only logs for a specific Spring app instance
+    #[clap(long)]
+    pub spring_instance: Vec<String>,
+
+    // Azure SQL Database
+    /// Show only errors, timeouts, blocks and deadlocks on a SQL server
+    #[clap(long)]
+    pub sql_server: Vec<String>,
+
+    // Azure Application Gateway and Front Door
+    /// Show only logs for a specific application gateway or front door
+    #[clap(long)]
+    pub gateway: Vec<String>,
+    /// Show only WAF events with this action (e.g. Blocked, Matched)
+    #[clap(long)]
+    pub waf_action: Vec<String>,
 }
 
 impl Opts {
@@ -96,16 +308,119 @@ impl Opts {
         if !(self.api_name.is_empty() && self.api_operation.is_empty()) {
             requested_services.push(Service::APIManagement);
         }
-        if !(self.function_app.is_empty() && self.function.is_empty()) {
+        if !(self.function_app.is_empty()
+            && self.function.is_empty()
+            && self.orchestration_id.is_empty())
+        {
             requested_services.push(Service::Functions);
         }
-        if !(self.container_group.is_empty() && self.container_name.is_empty()) {
+        if !(self.container_group.is_empty() && self.container_name.is_empty())
+            || self.container_service.is_some()
+        {
+            if self.container_service != Some(ContainerService::Instances) {
+                requested_services.push(Service::ContainerApps);
+            }
+            if self.container_service != Some(ContainerService::Apps) {
+                requested_services.push(Service::ContainerInstances);
+            }
+        }
+        if (self.system_only || self.console_only)
+            && !requested_services.contains(&Service::ContainerApps)
+        {
             requested_services.push(Service::ContainerApps);
         }
+        if !(self.workflow.is_empty() && self.workflow_run.is_empty()) {
+            requested_services.push(Service::LogicApps);
+        }
+        if !(self.computer.is_empty() && self.process.is_empty() && self.facility.is_empty()) {
+            requested_services.push(Service::Syslog);
+        }
+        if !(self.computer.is_empty() && self.event_log.is_empty() && self.event_id.is_empty()) {
+            requested_services.push(Service::WindowsEvents);
+        }
+        if !(self.web_app.is_empty() && self.slot.is_empty()) {
+            requested_services.push(Service::WebApps);
+        }
+        if !(self.namespace.is_empty()
+            && self.pod.is_empty()
+            && self.container.is_empty()
+            && self.verb.is_empty())
+        {
+            requested_services.push(Service::Kubernetes);
+        }
+        if !(self.resource_group.is_empty() && self.caller.is_empty()) {
+            requested_services.push(Service::ActivityLog);
+        }
+        if !self.dependency_type.is_empty() {
+            requested_services.push(Service::Dependencies);
+        }
+        if !self.event_name.is_empty() {
+            requested_services.push(Service::CustomEvents);
+        }
+        if !self.test_name.is_empty() {
+            requested_services.push(Service::Availability);
+        }
+        if !(self.user_principal.is_empty() && self.app_display_name.is_empty()) {
+            requested_services.push(Service::Identity);
+        }
+        if !(self.vault.is_empty() && self.secret_name.is_empty()) {
+            requested_services.push(Service::KeyVault);
+        }
+        if !(self.storage_account.is_empty() && self.status.is_empty() && self.operation.is_empty())
+        {
+            requested_services.push(Service::Storage);
+        }
+        if !(self.namespace_name.is_empty() && self.entity.is_empty()) {
+            requested_services.push(Service::Messaging);
+        }
+        if !(self.factory.is_empty() && self.pipeline.is_empty() && self.run_id.is_empty()) {
+            requested_services.push(Service::DataFactory);
+        }
+        if !(self.cosmos_account.is_empty()
+            && self.database.is_empty()
+            && self.collection.is_empty())
+        {
+            requested_services.push(Service::CosmosDb);
+        }
+        if !(self.spring_app.is_empty() && self.spring_instance.is_empty()) {
+            requested_services.push(Service::SpringApps);
+        }
+        // Cosmos DB and SQL Database both keep their data in databases, so
+        // --database applies to either.
+        if !(self.sql_server.is_empty() && self.database.is_empty()) {
+            requested_services.push(Service::SqlDatabase);
+        }
+        if !(self.gateway.is_empty() && self.waf_action.is_empty()) {
+            requested_services.push(Service::AppGateway);
+        }
+        // Functions already show all traces when unfiltered, so the generic
+        // application source is only used when asked for explicitly.
+        if !(self.role.is_empty() && self.role_instance.is_empty()) {
+            requested_services.push(Service::Application);
+        }
         if requested_services.is_empty() {
             requested_services.push(Service::APIManagement);
             requested_services.push(Service::Functions);
             requested_services.push(Service::ContainerApps);
+            requested_services.push(Service::ContainerInstances);
+            requested_services.push(Service::LogicApps);
+            requested_services.push(Service::Syslog);
+            requested_services.push(Service::WebApps);
+            requested_services.push(Service::Kubernetes);
+            requested_services.push(Service::ActivityLog);
+            requested_services.push(Service::Dependencies);
+            requested_services.push(Service::AppGateway);
+            requested_services.push(Service::Identity);
+            requested_services.push(Service::KeyVault);
+            requested_services.push(Service::Storage);
+            requested_services.push(Service::Messaging);
+            requested_services.push(Service::DataFactory);
+            requested_services.push(Service::CosmosDb);
+            requested_services.push(Service::WindowsEvents);
+            requested_services.push(Service::SqlDatabase);
+            requested_services.push(Service::SpringApps);
+            requested_services.push(Service::CustomEvents);
+            requested_services.push(Service::Availability);
         }
         requested_services
     }
@@ -130,7 +445,50 @@ where
         return Err(anyhow!(AzTailError::AppInsightsOrLogAnalytics));
     }
     if opts.workspace.is_none()
-        && (!opts.container_group.is_empty() || !opts.container_name.is_empty())
+        && (opts.container_service.is_some()
+            || opts.system_only
+            || opts.console_only
+            || [
+                &opts.container_group,
+                &opts.container_name,
+                &opts.workflow,
+                &opts.workflow_run,
+                &opts.computer,
+                &opts.process,
+                &opts.facility,
+                &opts.event_log,
+                &opts.event_id,
+                &opts.web_app,
+                &opts.slot,
+                &opts.namespace,
+                &opts.pod,
+                &opts.container,
+                &opts.verb,
+                &opts.resource_group,
+                &opts.caller,
+                &opts.gateway,
+                &opts.waf_action,
+                &opts.user_principal,
+                &opts.app_display_name,
+                &opts.vault,
+                &opts.secret_name,
+                &opts.storage_account,
+                &opts.status,
+                &opts.operation,
+                &opts.namespace_name,
+                &opts.entity,
+                &opts.factory,
+                &opts.pipeline,
+                &opts.run_id,
+                &opts.cosmos_account,
+                &opts.database,
+                &opts.collection,
+                &opts.sql_server,
+                &opts.spring_app,
+                &opts.spring_instance,
+            ]
+            .iter()
+            .any(|filter| !filter.is_empty()))
     {
         return Err(anyhow!(AzTailError::LogAnalyticsService));
     }
@@ -139,7 +497,8 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{options::cli_opts, testing::base_args};
+    use crate::options::{cli_opts, Service};
+    use crate::testing::{base_args, opsinsights_base_args};
     use speculoos::prelude::*;
 
     #[test]
@@ -176,4 +535,28 @@ mod test {
         let res = cli_opts(base_args().chain(vec!["--container-group", "ze-group"]));
         assert_that(&format!("{:?}", res.unwrap_err())).contains("use --workspace");
     }
+
+    #[test]
+    fn container_service_selects_container_source() {
+        let args = opsinsights_base_args().chain(vec!["--container-group", "ze-group"]);
+        let services = cli_opts(args).unwrap().requested_services();
+        assert_that(&services).contains(&Service::ContainerApps);
+        assert_that(&services).contains(&Service::ContainerInstances);
+        let args = opsinsights_base_args().chain(vec!["--container-service", "instances"]);
+        let services = cli_opts(args).unwrap().requested_services();
+        assert_that(&services).is_equal_to(vec![Service::ContainerInstances]);
+    }
+
+    #[test]
+    fn database_selects_cosmos_db_and_sql_database() {
+        let args = opsinsights_base_args().chain(vec!["--database", "orders"]);
+        let services = cli_opts(args).unwrap().requested_services();
+        assert_that(&services).is_equal_to(vec![Service::CosmosDb, Service::SqlDatabase]);
+    }
+
+    #[test]
+    fn workflow_requires_workspace() {
+        let res = cli_opts(base_args().chain(vec!["--workflow", "ze-workflow"]));
+        assert_that(&format!("{:?}", res.unwrap_err())).contains("use --workspace");
+    }
 }
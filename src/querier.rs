@@ -11,12 +11,21 @@ type QuerierArgs = (Vec<Box<dyn LogSource>>, Box<dyn Presenter>, bool);
 pub async fn querier((mut sources, presenter, follow): QuerierArgs) -> Result<QuerierArgs> {
     let mut source_max_ts = Vec::new();
     source_max_ts.resize(sources.len(), None::<DateTime<FixedOffset>>);
-    let streams = join_all(sources.iter().map(|source| source.stream()))
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?;
+    let mut results = join_all(sources.iter().map(|source| source.stream())).await;
+    if results.iter().all(Result::is_err) {
+        if let Some(Err(err)) = results.pop() {
+            return Err(err);
+        }
+    }
+    // Most sources read tables that only exist in workspaces where that
+    // service is deployed, so one failing query must not stop the others.
+    let streams = results.into_iter().map(|stream| {
+        stream.unwrap_or_else(|err| {
+            eprintln!("Skipping a log source: {:#}", err);
+            Box::new(std::iter::empty())
+        })
+    });
     let log_entries = streams
-        .into_iter()
         .enumerate()
         .map(|(source_id, stream)| stream.map(move |entry| (source_id, entry)))
         .kmerge_by(|(_, l), (_, r)| l < r);
@@ -45,6 +54,7 @@ mod test {
     use super::querier;
     use crate::examples::{T1, T2, T3, T4};
     use crate::kusto::Timespan;
+    use crate::source::LogSource;
     use crate::testing::*;
     use anyhow::Result;
     use speculoos::prelude::*;
@@ -100,6 +110,33 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn querier_skips_failing_sources() -> Result<()> {
+        let source1 = TestSource::with_rows(vec![log_entry(T1), log_entry(T3)]);
+        let source2 = TestSource::with_rows(vec![log_entry(T2)]);
+        let presented = Arc::new(Mutex::new(Vec::new()));
+        let presenter = TestPresenter::output_to(&presented);
+        let sources: Vec<Box<dyn LogSource>> = vec![source1, TestSource::failing(), source2];
+        let (mut sources, _, _) = querier((sources, presenter, true)).await?;
+        let res = Arc::try_unwrap(presented).unwrap().into_inner().unwrap();
+        assert_that(&res).has_length(3);
+        assert_that(&sources[2].get_query_mut().peek_timespan()).is_equal_to(&Timespan::new(
+            "timestamp".to_owned(),
+            T2.parse().ok(),
+            None,
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn querier_reports_error_when_every_source_fails() {
+        let presenter = TestPresenter::new();
+        match querier((vec![TestSource::failing()], presenter, true)).await {
+            Ok(_) => panic!("Expected querier to report the failure"),
+            Err(err) => assert_that(&err.to_string()).contains("ZeTable"),
+        };
+    }
+
     #[tokio::test]
     async fn querier_advances_start_time_individually_for_each_stream() -> Result<()> {
         let source1 = TestSource::with_rows(vec![log_entry(T1)]);
@@ -3,7 +3,7 @@ use crate::examples::traces_functions_row;
 use crate::kusto::{Ordering, Query, Timespan};
 use crate::output::Presenter;
 use crate::source::{LogEntry, LogSource};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::Value;
 use std::sync::{Arc, Mutex};
@@ -39,6 +39,7 @@ pub fn log_entry<'a>(timestamp: &'a str) -> LogEntry {
 pub struct TestSource {
     pub query: Query,
     pub results: Mutex<Vec<LogEntry>>,
+    pub fails: bool,
 }
 
 impl TestSource {
@@ -46,6 +47,7 @@ impl TestSource {
         Box::new(Self {
             query: some_query(),
             results: Mutex::new(vec![log_entry("2021-11-20T06:18:30+00:00")]),
+            fails: false,
         })
     }
 
@@ -53,6 +55,15 @@ impl TestSource {
         Box::new(Self {
             query: some_query(),
             results: Mutex::new(rows),
+            fails: false,
+        })
+    }
+
+    pub fn failing() -> Box<Self> {
+        Box::new(Self {
+            query: some_query(),
+            results: Mutex::new(Vec::new()),
+            fails: true,
         })
     }
 }
@@ -60,6 +71,11 @@ impl TestSource {
 #[async_trait]
 impl LogSource for TestSource {
     async fn stream(&self) -> Result<Box<dyn Iterator<Item = LogEntry>>> {
+        if self.fails {
+            return Err(anyhow!(
+                "Failed to resolve table expression named 'ZeTable'"
+            ));
+        }
         let res = self.results.lock().unwrap().clone();
         Ok(Box::new(res.into_iter()))
     }